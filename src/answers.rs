@@ -0,0 +1,221 @@
+use crate::error::Error;
+use crate::loadable::read_to_string;
+use crate::solver::{Answer, Part, DEFAULT_YEAR};
+use std::collections::BTreeMap;
+
+pub const DEFAULT_ANSWERS_FILENAME: &str = "answers.toml";
+
+// Days of other years than |DEFAULT_YEAR| are checked against a manifest of
+// their own, since answers are keyed by day.
+pub fn default_filename(year: u32) -> String {
+    if year == DEFAULT_YEAR {
+        DEFAULT_ANSWERS_FILENAME.to_owned()
+    } else {
+        format!("answers_{}.toml", year)
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DayAnswers {
+    // Input the answers belong to, the bundled input if None.
+    pub input: Option<String>,
+    pub part_one: Option<Answer>,
+    pub part_two: Option<Answer>,
+}
+
+impl DayAnswers {
+    pub fn get(&self, part: Part) -> Option<&Answer> {
+        match part {
+            Part::One => self.part_one.as_ref(),
+            Part::Two => self.part_two.as_ref(),
+        }
+    }
+}
+
+// Known good answers, keyed by day. Stored as a small subset of TOML:
+//
+//   [day.8]
+//   input = "input/day_eight.txt"
+//   part_one = 1814
+//   part_two = "text answers are quoted"
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Answers {
+    pub days: BTreeMap<u32, DayAnswers>,
+}
+
+fn parse_header(line: &str) -> Result<u32, Error> {
+    line.strip_prefix("[day.")
+        .and_then(|l| l.strip_suffix(']'))
+        .ok_or_else(|| Error::parse("expected a header like \"[day.8]\""))?
+        .parse::<u32>()
+        .map_err(|e| Error::from(e).at_column("[day.".len() + 1))
+}
+
+fn parse_string(value: &str) -> Option<String> {
+    let inner = value.strip_prefix('"')?.strip_suffix('"')?;
+    // Keep it simple: no escapes, so no quotes or backslashes inside.
+    if inner.contains(['"', '\\']) {
+        return None;
+    }
+    Some(inner.to_owned())
+}
+
+fn parse_answer(value: &str) -> Option<Answer> {
+    match parse_string(value) {
+        Some(text) => Some(Answer::Text(text)),
+        None => value.parse::<i64>().ok().map(Answer::Number),
+    }
+}
+
+impl Answers {
+    pub fn parse(contents: &str) -> Result<Answers, Error> {
+        let mut answers = Answers::default();
+        let mut current = None;
+        for (i, line) in contents.lines().enumerate() {
+            let at_line = |e: Error| e.at_line(i + 1);
+            // Comments are only supported on their own line.
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') {
+                let day = parse_header(line).map_err(at_line)?;
+                if answers.days.insert(day, DayAnswers::default()).is_some() {
+                    return Err(at_line(Error::parse(format!(
+                        "day {} is listed twice",
+                        day
+                    ))));
+                }
+                current = Some(day);
+                continue;
+            }
+
+            let day = current.ok_or_else(|| at_line(Error::parse("expected a [day.N] header")))?;
+            let entry = answers.days.get_mut(&day).unwrap();
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| at_line(Error::parse("expected \"key = value\"")))?;
+            let value_column = line.len() - value.trim_start().len() + 1;
+            let (key, value) = (key.trim(), value.trim());
+            let invalid = |what: &str| {
+                at_line(Error::parse(format!("expected {}", what)).at_column(value_column))
+            };
+            match key {
+                "input" => {
+                    entry.input = Some(parse_string(value).ok_or_else(|| invalid("a string"))?)
+                }
+                "part_one" => {
+                    entry.part_one =
+                        Some(parse_answer(value).ok_or_else(|| invalid("a number or string"))?)
+                }
+                "part_two" => {
+                    entry.part_two =
+                        Some(parse_answer(value).ok_or_else(|| invalid("a number or string"))?)
+                }
+                _ => {
+                    return Err(at_line(
+                        Error::parse(format!("unknown key '{}'", key)).at_column(1),
+                    ))
+                }
+            }
+        }
+        Ok(answers)
+    }
+
+    pub fn load(filename: &str) -> Result<Answers, Error> {
+        Answers::parse(&read_to_string(filename)?).map_err(|e| e.in_file(filename))
+    }
+
+    pub fn get(&self, day: u32) -> Option<&DayAnswers> {
+        self.days.get(&day)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail { expected: Answer, actual: Answer },
+    // There is no known answer to compare against.
+    Unsolved,
+}
+
+pub fn verify(expected: Option<&Answer>, actual: &Answer) -> Verdict {
+    match expected {
+        None => Verdict::Unsolved,
+        Some(expected) if expected == actual => Verdict::Pass,
+        Some(expected) => Verdict::Fail {
+            expected: expected.clone(),
+            actual: actual.clone(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let answers = Answers::parse(
+            "# comment\n\n[day.8]\ninput = \"other.txt\"\npart_one = 1814\n\n[day.25]\npart_two = \"ABC\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            Some(&DayAnswers {
+                input: Some("other.txt".to_owned()),
+                part_one: Some(Answer::Number(1814)),
+                part_two: None,
+            }),
+            answers.get(8)
+        );
+        assert_eq!(
+            Some(&Answer::Text("ABC".to_owned())),
+            answers.get(25).unwrap().get(Part::Two)
+        );
+        assert_eq!(None, answers.get(1));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |s| Answers::parse(s).unwrap_err().to_string();
+        assert_eq!("1: expected a [day.N] header", error("part_one = 1"));
+        assert_eq!(
+            "2:12: expected a number or string",
+            error("[day.1]\npart_one = one")
+        );
+        assert_eq!(
+            "2:1: unknown key 'part_three'",
+            error("[day.1]\npart_three = 1")
+        );
+        assert_eq!("3: day 1 is listed twice", error("[day.1]\n\n[day.1]"));
+        assert_eq!(
+            "1:6: invalid number: invalid digit found in string",
+            error("[day.x]")
+        );
+    }
+
+    #[test]
+    fn test_bundled_answers() {
+        let answers = Answers::load(DEFAULT_ANSWERS_FILENAME).unwrap();
+        assert_eq!(
+            Some(&Answer::Number(1056)),
+            answers.get(8).unwrap().get(Part::Two)
+        );
+        assert_eq!(DEFAULT_ANSWERS_FILENAME, default_filename(DEFAULT_YEAR));
+        assert_eq!("answers_2021.toml", default_filename(2021));
+    }
+
+    #[test]
+    fn test_verify() {
+        let answer = Answer::Number(3);
+        assert_eq!(Verdict::Unsolved, verify(None, &answer));
+        assert_eq!(Verdict::Pass, verify(Some(&answer), &answer));
+        assert_eq!(
+            Verdict::Fail {
+                expected: Answer::Number(4),
+                actual: answer.clone()
+            },
+            verify(Some(&Answer::Number(4)), &answer)
+        );
+    }
+}
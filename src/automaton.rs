@@ -0,0 +1,678 @@
+use crate::error::Error;
+use crate::pattern::split_with_columns;
+use crate::yet_another_geometry_mod::{
+    ArrayLike, DenseGrid, Direction, Direction8, HexDirection, Matrix2D, Point, Point2D, Point3D,
+    SparseGrid, UnitOffset,
+};
+use std::collections::HashMap;
+use std::fmt;
+use std::hash::Hash;
+use std::ops::Add;
+use std::str::FromStr;
+
+// Which cells count as the neighbours of a cell. Every topology here is
+// symmetric, i.e. if a is a neighbour of b then b is a neighbour of a, which
+// |SparseAutomaton| relies on.
+pub trait Topology<P> {
+    fn neighbors(&self, cell: &P) -> impl Iterator<Item = P>;
+}
+
+// Neighbours at fixed offsets from every cell, like the eight around a
+// square or the six around a hexagon.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Neighborhood<P> {
+    pub offsets: Vec<P>,
+}
+
+impl<P> Neighborhood<P> {
+    pub fn new(offsets: Vec<P>) -> Neighborhood<P> {
+        Neighborhood { offsets }
+    }
+}
+
+impl Neighborhood<Point2D> {
+    // Up, right, down and left.
+    pub fn square4() -> Neighborhood<Point2D> {
+        Neighborhood::new(Direction::ALL.iter().map(|d| d.offset()).collect())
+    }
+
+    // Diagonals too.
+    pub fn square8() -> Neighborhood<Point2D> {
+        Neighborhood::new(Direction8::iter().map(|d| d.offset()).collect())
+    }
+}
+
+impl<const N: usize> Neighborhood<Point<N>> {
+    // Every point touching a cell, including diagonally.
+    pub fn moore() -> Neighborhood<Point<N>> {
+        Neighborhood::new(Point::<N>::neighbor_offsets())
+    }
+
+    // Only the points sharing a face with a cell.
+    pub fn von_neumann() -> Neighborhood<Point<N>> {
+        Neighborhood::new(Point::<N>::orthogonal_offsets())
+    }
+}
+
+impl Neighborhood<Point3D> {
+    // The six hexagons around a hexagon, in cube coordinates.
+    pub fn hex() -> Neighborhood<Point3D> {
+        Neighborhood::new(HexDirection::iter().map(|d| d.offset()).collect())
+    }
+}
+
+impl<P: Copy + Add<Output = P>> Topology<P> for Neighborhood<P> {
+    fn neighbors(&self, cell: &P) -> impl Iterator<Item = P> {
+        let cell = *cell;
+        self.offsets.iter().map(move |offset| cell + *offset)
+    }
+}
+
+// The first cell seen in each of the eight directions, looking past
+// anything that isn't a cell, like the seats of day eleven that can see
+// each other across the floor. Worked out up front, so the layout can't
+// change afterwards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LineOfSight {
+    neighbors: Matrix2D<Vec<Point2D>>,
+}
+
+impl LineOfSight {
+    pub fn new<T, F>(matrix: &Matrix2D<T>, is_cell: F) -> LineOfSight
+    where
+        F: Fn(&T) -> bool,
+    {
+        let neighbors = Matrix2D::from_fn(matrix.size, |location| {
+            Direction8::iter()
+                .filter_map(|direction| {
+                    matrix
+                        .ray_until(&location, direction, |value| is_cell(value))
+                        .last()
+                        .filter(|(_, value)| is_cell(value))
+                        .map(|(neighbor, _)| neighbor)
+                })
+                .collect()
+        });
+        LineOfSight { neighbors }
+    }
+}
+
+impl Topology<Point2D> for LineOfSight {
+    fn neighbors(&self, cell: &Point2D) -> impl Iterator<Item = Point2D> {
+        self.neighbors.try_get(cell).into_iter().flatten().copied()
+    }
+}
+
+// 0 is dead and 1 is alive. Rules with more states have cells take a few
+// more steps to die, see |Rule|.
+pub type State = u8;
+pub const DEAD: State = 0;
+pub const ALIVE: State = 1;
+
+// Which numbers of live neighbours bring a dead cell to life, and which let
+// a live cell survive. Written the usual way, e.g. "B3/S23" for Conway's
+// Game of Life.
+//
+// "Generations" rules like "B2/S/C3" have more than two |states|: a live
+// cell that doesn't survive goes through states 2, 3 and so on before it is
+// dead, and can't come back to life on the way. Only live cells count as
+// neighbours.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    // Both sorted.
+    pub birth: Vec<usize>,
+    pub survival: Vec<usize>,
+    pub states: State,
+}
+
+impl Rule {
+    pub fn new(birth: &[usize], survival: &[usize]) -> Rule {
+        Rule::generations(birth, survival, 2)
+    }
+
+    pub fn generations(birth: &[usize], survival: &[usize], states: State) -> Rule {
+        assert!(states >= 2, "rules need at least 2 states");
+        let sorted = |counts: &[usize]| {
+            let mut counts = counts.to_vec();
+            counts.sort_unstable();
+            counts.dedup();
+            counts
+        };
+        Rule {
+            birth: sorted(birth),
+            survival: sorted(survival),
+            states,
+        }
+    }
+
+    // Conway's Game of Life.
+    pub fn life() -> Rule {
+        Rule::new(&[3], &[2, 3])
+    }
+
+    pub fn next(&self, state: State, live_neighbors: usize) -> State {
+        match state {
+            DEAD if self.birth.contains(&live_neighbors) => ALIVE,
+            DEAD => DEAD,
+            ALIVE if self.survival.contains(&live_neighbors) => ALIVE,
+            // Dying, which wraps around to dead for two state rules.
+            _ => (state + 1) % self.states,
+        }
+    }
+}
+
+// Counts are single digits like in "S23", unless they are separated by
+// commas for neighbourhoods with ten or more cells, like "S2,3,10".
+fn parse_counts(s: &str, column: usize) -> Result<Vec<usize>, Error> {
+    if s.contains(',') {
+        return split_with_columns(s, ",")
+            .map(|(offset, count)| {
+                count
+                    .parse::<usize>()
+                    .map_err(|e| Error::from(e).at_column(column + offset - 1))
+            })
+            .collect();
+    }
+    s.char_indices()
+        .map(|(i, c)| {
+            c.to_digit(10).map(|d| d as usize).ok_or_else(|| {
+                Error::parse(format!("unexpected '{}' in rule", c)).at_column(column + i)
+            })
+        })
+        .collect()
+}
+
+fn format_counts(counts: &[usize]) -> String {
+    let separator = if counts.iter().any(|c| *c > 9) {
+        ","
+    } else {
+        ""
+    };
+    counts
+        .iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+impl FromStr for Rule {
+    type Err = Error;
+
+    // Letters can be either case, so "b3/s23" works too.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let expected = |column| {
+            Error::parse("expected a rule like \"B3/S23\" or \"B2/S/C3\"").at_column(column)
+        };
+        let parts: Vec<(usize, &str)> = split_with_columns(s, "/").collect();
+        if parts.len() > 3 {
+            return Err(expected(parts[3].0));
+        }
+        let mut fields = [None; 3];
+        for (i, prefix) in ['B', 'S', 'C'].iter().enumerate() {
+            if let Some((column, part)) = parts.get(i) {
+                if !part.starts_with([*prefix, prefix.to_ascii_lowercase()]) {
+                    return Err(expected(*column));
+                }
+                fields[i] = Some((column + 1, &part[1..]));
+            }
+        }
+        let (birth, survival) = match fields {
+            [Some(birth), Some(survival), _] => (birth, survival),
+            _ => return Err(expected(s.len() + 1)),
+        };
+        let states = match fields[2] {
+            Some((column, states)) => match states.parse::<State>() {
+                Ok(states) if states >= 2 => states,
+                Ok(_) => return Err(Error::parse("rules need at least 2 states").at_column(column)),
+                Err(e) => return Err(Error::from(e).at_column(column)),
+            },
+            None => 2,
+        };
+        Ok(Rule::generations(
+            &parse_counts(birth.1, birth.0)?,
+            &parse_counts(survival.1, survival.0)?,
+            states,
+        ))
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "B{}/S{}",
+            format_counts(&self.birth),
+            format_counts(&self.survival)
+        )?;
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+        Ok(())
+    }
+}
+
+// Another rule or number of steps to run a day's automaton with, for trying
+// things out without recompiling. Anything that's None is left up to the
+// day.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Experiment {
+    pub rule: Option<Rule>,
+    pub steps: Option<usize>,
+}
+
+impl Experiment {
+    // The rule to run with, |rule| being the day's own.
+    pub fn rule_or(&self, rule: &str) -> Rule {
+        self.rule
+            .clone()
+            .unwrap_or_else(|| rule.parse().expect("invalid built in rule"))
+    }
+
+    pub fn steps_or(&self, steps: usize) -> usize {
+        self.steps.unwrap_or(steps)
+    }
+}
+
+// The state after |start| steps comes back every |length| steps. A stable
+// automaton has a cycle of length one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: usize,
+    pub length: usize,
+}
+
+// What every automaton can do regardless of how it stores its cells.
+pub trait Automaton {
+    // Everything needed to tell two states apart.
+    type Snapshot: Eq + Hash;
+
+    // Moves every cell forward at once, returning whether any changed.
+    fn step(&mut self) -> bool;
+
+    fn population(&self) -> usize;
+
+    fn snapshot(&self) -> Self::Snapshot;
+
+    fn step_n(&mut self, n: usize) {
+        for _ in 0..n {
+            // Nothing will ever change again.
+            if !self.step() {
+                break;
+            }
+        }
+    }
+
+    // Returns how many steps changed something, or None if it was still
+    // changing after |max_steps|. Unlike |find_cycle| this keeps no history,
+    // so it's cheap to run for as long as it takes.
+    fn run_until_stable(&mut self, max_steps: usize) -> Option<usize> {
+        // One more step than allowed to see whether the last one settled it.
+        (0..=max_steps).find(|_| !self.step())
+    }
+
+    // Steps until a state repeats, or None if none did within |max_steps|.
+    // Leaves the automaton at the first repeat.
+    fn find_cycle(&mut self, max_steps: usize) -> Option<Cycle> {
+        let mut seen = HashMap::new();
+        for steps in 0..=max_steps {
+            if let Some(start) = seen.insert(self.snapshot(), steps) {
+                return Some(Cycle {
+                    start,
+                    length: steps - start,
+                });
+            }
+            self.step();
+        }
+        None
+    }
+}
+
+// Keeps only the cells that aren't dead, so the world is unbounded and the
+// cost of a step depends on how many are alive rather than on how much space
+// they span.
+#[derive(Debug, Clone)]
+pub struct SparseAutomaton<P: Eq + Hash, T> {
+    cells: SparseGrid<P, State>,
+    topology: T,
+    rule: Rule,
+}
+
+impl<P, T> SparseAutomaton<P, T>
+where
+    P: ArrayLike + Copy + Eq + Hash,
+    T: Topology<P>,
+{
+    // Starts with the |alive| cells and every other one dead. Only cells
+    // next to live ones are looked at, so the rule can't have anything born
+    // without live neighbours.
+    pub fn new<I>(alive: I, topology: T, rule: Rule) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = P>,
+    {
+        if rule.birth.contains(&0) {
+            return Err(Error::parse(format!(
+                "{} has cells born from nothing, which needs a bounded world",
+                rule
+            )));
+        }
+        Ok(SparseAutomaton {
+            cells: alive.into_iter().map(|cell| (cell, ALIVE)).collect(),
+            topology,
+            rule,
+        })
+    }
+
+    // Every cell that isn't dead, and the box around them.
+    pub fn cells(&self) -> &SparseGrid<P, State> {
+        &self.cells
+    }
+
+    pub fn state(&self, cell: &P) -> State {
+        self.cells.get(cell).copied().unwrap_or(DEAD)
+    }
+
+    pub fn is_alive(&self, cell: &P) -> bool {
+        self.state(cell) == ALIVE
+    }
+
+    // In no particular order.
+    pub fn alive(&self) -> impl Iterator<Item = &P> {
+        self.cells
+            .iter()
+            .filter(|(_, state)| **state == ALIVE)
+            .map(|(cell, _)| cell)
+    }
+}
+
+impl<P, T> Automaton for SparseAutomaton<P, T>
+where
+    P: ArrayLike + Copy + Eq + Hash + Ord,
+    T: Topology<P>,
+{
+    // Sorted, since maps can't be hashed.
+    type Snapshot = Vec<(P, State)>;
+
+    fn step(&mut self) -> bool {
+        let mut counts = HashMap::<P, usize>::new();
+        for (cell, state) in self.cells.iter() {
+            // Cells without live neighbours still get a say.
+            counts.entry(*cell).or_insert(0);
+            if *state == ALIVE {
+                for neighbor in self.topology.neighbors(cell) {
+                    *counts.entry(neighbor).or_insert(0) += 1;
+                }
+            }
+        }
+        let next: SparseGrid<P, State> = counts
+            .into_iter()
+            .map(|(cell, count)| (cell, self.rule.next(self.state(&cell), count)))
+            .filter(|(_, state)| *state != DEAD)
+            .collect();
+        let changed = next != self.cells;
+        self.cells = next;
+        changed
+    }
+
+    fn population(&self) -> usize {
+        self.alive().count()
+    }
+
+    fn snapshot(&self) -> Vec<(P, State)> {
+        let mut cells: Vec<(P, State)> = self.cells.iter().map(|(p, s)| (*p, *s)).collect();
+        cells.sort_unstable();
+        cells
+    }
+}
+
+// Keeps every cell of a fixed size matrix. Locations that are None aren't
+// cells at all: they never change and never count as neighbours, like the
+// floor between seats or anything outside the matrix.
+#[derive(Debug, Clone)]
+pub struct DenseAutomaton<T> {
+    cells: DenseGrid<Point2D, Option<State>>,
+    // Where the next step is built, to save allocating one every time.
+    next: DenseGrid<Point2D, Option<State>>,
+    topology: T,
+    rule: Rule,
+}
+
+impl<T: Topology<Point2D>> DenseAutomaton<T> {
+    pub fn new(cells: Matrix2D<Option<State>>, topology: T, rule: Rule) -> Self {
+        // Nothing is ever set outside the matrix, so it never grows.
+        let cells = DenseGrid::from_matrix(cells, None, 0);
+        DenseAutomaton {
+            next: cells.clone(),
+            cells,
+            topology,
+            rule,
+        }
+    }
+
+    pub fn cells(&self) -> &DenseGrid<Point2D, Option<State>> {
+        &self.cells
+    }
+}
+
+impl<T: Topology<Point2D>> Automaton for DenseAutomaton<T> {
+    type Snapshot = Matrix2D<Option<State>>;
+
+    fn step(&mut self) -> bool {
+        let DenseAutomaton {
+            cells,
+            next,
+            topology,
+            rule,
+        } = self;
+        let mut changed = false;
+        // Both have the same region, so their cells line up.
+        for ((location, cell), slot) in cells.iter().zip(next.values_mut()) {
+            if let Some(state) = *cell {
+                let live_neighbors = topology
+                    .neighbors(&location)
+                    .filter(|neighbor| *cells.get(neighbor) == Some(ALIVE))
+                    .count();
+                let state = rule.next(state, live_neighbors);
+                changed |= *cell != Some(state);
+                *slot = Some(state);
+            }
+        }
+        std::mem::swap(cells, next);
+        changed
+    }
+
+    fn population(&self) -> usize {
+        self.cells
+            .iter()
+            .filter(|(_, cell)| **cell == Some(ALIVE))
+            .count()
+    }
+
+    fn snapshot(&self) -> Matrix2D<Option<State>> {
+        self.cells.to_matrix()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::yet_another_geometry_mod::Bounds;
+
+    fn points(cells: &[(i64, i64)]) -> Vec<Point2D> {
+        cells
+            .iter()
+            .map(|(x, y)| Point2D { x: *x, y: *y })
+            .collect()
+    }
+
+    fn dense(rows: &[&str]) -> Matrix2D<Option<State>> {
+        Matrix2D::from_fn(
+            Point2D {
+                x: rows[0].len() as i64,
+                y: rows.len() as i64,
+            },
+            |p| match rows[p.y as usize].as_bytes()[p.x as usize] {
+                b'#' => Some(ALIVE),
+                b'.' => Some(DEAD),
+                b'-' => Some(2),
+                _ => None,
+            },
+        )
+    }
+
+    #[test]
+    fn test_neighborhoods() {
+        let origin = Point2D::default();
+        assert_eq!(4, Neighborhood::square4().neighbors(&origin).count());
+        assert_eq!(8, Neighborhood::square8().neighbors(&origin).count());
+        assert_eq!(80, Neighborhood::<Point<4>>::moore().offsets.len());
+        assert_eq!(6, Neighborhood::<Point<3>>::von_neumann().offsets.len());
+        let hex = Neighborhood::hex();
+        assert!(hex
+            .neighbors(&Point3D::default())
+            .all(|p| p.x + p.y + p.z == 0));
+    }
+
+    #[test]
+    fn test_line_of_sight() {
+        let matrix = dense(&["#  .", "    ", "# # "]);
+        let sight = LineOfSight::new(&matrix, |c| c.is_some());
+        let mut seen: Vec<Point2D> = sight.neighbors(&Point2D { x: 0, y: 0 }).collect();
+        seen.sort();
+        assert_eq!(points(&[(0, 2), (2, 2), (3, 0)]), seen);
+        assert_eq!(0, sight.neighbors(&Point2D { x: 9, y: 9 }).count());
+    }
+
+    #[test]
+    fn test_sparse() {
+        let glider = points(&[(1, 0), (2, 1), (0, 2), (1, 2), (2, 2)]);
+        let mut life =
+            SparseAutomaton::new(glider.clone(), Neighborhood::square8(), Rule::life()).unwrap();
+        life.step_n(4);
+        // Gliders move one step diagonally every four.
+        let mut moved: Vec<Point2D> = glider.iter().map(|p| *p + Point2D { x: 1, y: 1 }).collect();
+        moved.sort();
+        let mut alive: Vec<Point2D> = life.alive().copied().collect();
+        alive.sort();
+        assert_eq!(moved, alive);
+        assert_eq!(
+            Some(Bounds {
+                min: Point2D { x: 1, y: 1 },
+                max: Point2D { x: 3, y: 3 },
+            }),
+            life.cells().bounds()
+        );
+        // And never repeat.
+        assert_eq!(None, life.find_cycle(20));
+
+        let blinker = points(&[(0, 0), (1, 0), (2, 0)]);
+        let mut life =
+            SparseAutomaton::new(blinker, Neighborhood::square8(), Rule::life()).unwrap();
+        assert_eq!(
+            Some(Cycle {
+                start: 0,
+                length: 2
+            }),
+            life.find_cycle(10)
+        );
+        assert_eq!(3, life.population());
+    }
+
+    #[test]
+    fn test_sparse_birth_from_nothing() {
+        let error = SparseAutomaton::new(vec![], Neighborhood::square4(), Rule::new(&[0], &[]));
+        assert_eq!(
+            "B0/S has cells born from nothing, which needs a bounded world",
+            error.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let rule = |s: &str| s.parse::<Rule>().unwrap();
+        assert_eq!(Rule::life(), rule("B3/S23"));
+        assert_eq!(Rule::new(&[2], &[1, 2]), rule("b2/s21"));
+        assert_eq!(Rule::generations(&[2], &[], 3), rule("B2/S/C3"));
+        assert_eq!(Rule::new(&[3, 10], &[2]), rule("B10,3/S2"));
+        for s in &["B3/S23", "B2/S/C3", "B0/S0123", "B3,10/S2/C25"] {
+            assert_eq!(*s, rule(s).to_string());
+        }
+
+        let error = |s: &str| s.parse::<Rule>().unwrap_err().to_string();
+        let expected = "expected a rule like \"B3/S23\" or \"B2/S/C3\"";
+        assert_eq!(format!("column 1: {}", expected), error("3/23"));
+        assert_eq!(format!("column 3: {}", expected), error("B3"));
+        assert_eq!(format!("column 4: {}", expected), error("B3/23"));
+        assert_eq!(format!("column 11: {}", expected), error("B3/S23/C3/D"));
+        assert_eq!("column 5: unexpected 'x' in rule", error("B3/Sx"));
+        assert_eq!(
+            "column 4: invalid number: invalid digit found in string",
+            error("B3,x/S2")
+        );
+        assert_eq!("column 9: rules need at least 2 states", error("B3/S23/C1"));
+    }
+
+    #[test]
+    fn test_generations() {
+        // Brian's Brain: every live cell dies, spending a step dying first.
+        let rule: Rule = "B2/S/C3".parse().unwrap();
+        assert_eq!(ALIVE, rule.next(DEAD, 2));
+        assert_eq!(2, rule.next(ALIVE, 2));
+        assert_eq!(DEAD, rule.next(2, 2));
+        assert_eq!(DEAD, Rule::life().next(ALIVE, 4));
+
+        let mut brain = DenseAutomaton::new(
+            dense(&["....", ".##.", ".--.", "...."]),
+            Neighborhood::square8(),
+            rule.clone(),
+        );
+        brain.step();
+        // Dying cells don't count as neighbours, so only the top row is born.
+        assert_eq!(
+            dense(&[".##.", ".--.", "....", "...."]),
+            brain.cells().to_matrix()
+        );
+
+        let mut brain =
+            SparseAutomaton::new(points(&[(1, 1), (2, 1)]), Neighborhood::square8(), rule).unwrap();
+        brain.step();
+        assert_eq!(2, brain.state(&Point2D { x: 1, y: 1 }));
+        assert!(brain.is_alive(&Point2D { x: 1, y: 0 }));
+        assert_eq!(4, brain.population());
+    }
+
+    #[test]
+    fn test_dense() {
+        let mut life = DenseAutomaton::new(
+            dense(&[".....", "..#..", "..#..", "..#..", "....."]),
+            Neighborhood::square8(),
+            Rule::life(),
+        );
+        assert!(life.step());
+        assert_eq!(
+            dense(&[".....", ".....", ".###.", ".....", "....."]),
+            life.cells().to_matrix()
+        );
+        assert_eq!(
+            Some(Cycle {
+                start: 0,
+                length: 2
+            }),
+            life.find_cycle(10)
+        );
+        // A blinker never settles.
+        assert_eq!(None, life.run_until_stable(10));
+
+        // Cells that aren't there can't be born, so this never fills in.
+        let mut life =
+            DenseAutomaton::new(dense(&["##", "# "]), Neighborhood::square8(), Rule::life());
+        assert_eq!(Some(0), life.run_until_stable(10));
+        assert_eq!(3, life.population());
+        assert_eq!(
+            Some(Cycle {
+                start: 0,
+                length: 1
+            }),
+            life.find_cycle(10)
+        );
+    }
+}
@@ -0,0 +1,198 @@
+use crate::error::Error;
+use crate::loadable::load_lines;
+use crate::report;
+use crate::solver::{DynSolver, Part};
+use std::collections::BTreeMap;
+use std::fs;
+use std::time::Duration;
+
+pub const DEFAULT_RUNS: usize = 10;
+pub const DEFAULT_WARMUP: usize = 2;
+pub const DEFAULT_THRESHOLD_PERCENT: f64 = 10.0;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Stats {
+    pub min: Duration,
+    pub median: Duration,
+    pub mean: Duration,
+    pub stddev: Duration,
+}
+
+impl Stats {
+    pub fn from_samples(samples: &[Duration]) -> Stats {
+        assert!(!samples.is_empty(), "need at least one sample");
+        let mut sorted = samples.to_vec();
+        sorted.sort();
+
+        let middle = sorted.len() / 2;
+        let median = if sorted.len().is_multiple_of(2) {
+            (sorted[middle - 1] + sorted[middle]) / 2
+        } else {
+            sorted[middle]
+        };
+
+        let nanos: Vec<f64> = sorted.iter().map(|d| d.as_nanos() as f64).collect();
+        let mean = nanos.iter().sum::<f64>() / nanos.len() as f64;
+        // Population standard deviation, we have every sample we care about.
+        let variance = nanos.iter().map(|n| (n - mean).powi(2)).sum::<f64>() / nanos.len() as f64;
+
+        Stats {
+            min: sorted[0],
+            median,
+            mean: Duration::from_nanos(mean.round() as u64),
+            stddev: Duration::from_nanos(variance.sqrt().round() as u64),
+        }
+    }
+}
+
+// Solves the day |warmup| times without measuring, then |runs| times,
+// returning how long each measured run took from parsing to the last part.
+pub fn sample(
+    solver: &dyn DynSolver,
+    input: Option<&str>,
+    part: Option<Part>,
+    warmup: usize,
+    runs: usize,
+) -> Result<Vec<Duration>, String> {
+    let mut samples = Vec::with_capacity(runs);
+    for i in 0..warmup + runs {
+        let records = report::solve_timed(solver, input, part);
+        if let Some(reason) = report::first_error(&records) {
+            return Err(reason.to_owned());
+        }
+        if i >= warmup {
+            samples.push(report::total_time(&records));
+        }
+    }
+    Ok(samples)
+}
+
+// Median time per day, as saved by a previous bench run. The file has one
+// "<day> <median in ns>" pair per line.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Baseline {
+    pub medians: BTreeMap<u32, Duration>,
+}
+
+impl Baseline {
+    pub fn load(filename: &str) -> Result<Baseline, Error> {
+        let entries = load_lines(filename, |l| {
+            let mut fields = l.split_whitespace();
+            let (day, median) = match (fields.next(), fields.next(), fields.next()) {
+                (Some(day), Some(median), None) => (day, median),
+                _ => return Err(Error::parse("expected \"<day> <median in ns>\"")),
+            };
+            let day = day
+                .parse::<u32>()
+                .map_err(|e| Error::from(e).at_column(1))?;
+            let median = median
+                .parse::<u64>()
+                .map_err(|e| Error::from(e).at_column(l.rfind(median).unwrap() + 1))?;
+            Ok((day, Duration::from_nanos(median)))
+        })?;
+        Ok(Baseline {
+            medians: entries.into_iter().collect(),
+        })
+    }
+
+    pub fn save(&self, filename: &str) -> Result<(), Error> {
+        let contents: String = self
+            .medians
+            .iter()
+            .map(|(day, median)| format!("{} {}\n", day, median.as_nanos()))
+            .collect();
+        fs::write(filename, contents).map_err(|err| Error::io(filename, err))
+    }
+}
+
+// Percentage change from |baseline| to |current|, positive when slower. None
+// if |baseline| is zero, since nothing is a percentage of that.
+pub fn change_percent(baseline: Duration, current: Duration) -> Option<f64> {
+    if baseline.is_zero() {
+        return None;
+    }
+    let baseline = baseline.as_nanos() as f64;
+    Some((current.as_nanos() as f64 - baseline) / baseline * 100.0)
+}
+
+pub fn is_regression(baseline: Duration, current: Duration, threshold_percent: f64) -> bool {
+    change_percent(baseline, current).is_some_and(|change| change > threshold_percent)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::Registry;
+
+    fn millis(values: &[u64]) -> Vec<Duration> {
+        values.iter().map(|v| Duration::from_millis(*v)).collect()
+    }
+
+    #[test]
+    fn test_stats() {
+        let stats = Stats::from_samples(&millis(&[9, 2, 5, 4, 4, 4, 7, 5]));
+        assert_eq!(Duration::from_millis(2), stats.min);
+        assert_eq!(
+            Duration::from_millis(4) + Duration::from_micros(500),
+            stats.median
+        );
+        assert_eq!(Duration::from_millis(5), stats.mean);
+        assert_eq!(Duration::from_millis(2), stats.stddev);
+
+        let stats = Stats::from_samples(&millis(&[3, 1, 2]));
+        assert_eq!(Duration::from_millis(2), stats.median);
+    }
+
+    #[test]
+    fn test_regressions() {
+        let baseline = Duration::from_millis(100);
+        assert_eq!(
+            Some(20.0),
+            change_percent(baseline, Duration::from_millis(120))
+        );
+        assert_eq!(
+            Some(-50.0),
+            change_percent(baseline, Duration::from_millis(50))
+        );
+        assert!(is_regression(baseline, Duration::from_millis(120), 10.0));
+        assert!(!is_regression(baseline, Duration::from_millis(105), 10.0));
+        assert!(!is_regression(baseline, Duration::from_millis(50), 10.0));
+    }
+
+    #[test]
+    fn test_zero_baseline() {
+        let zero = Duration::default();
+        assert_eq!(None, change_percent(zero, Duration::from_millis(5)));
+        assert_eq!(None, change_percent(zero, zero));
+        assert!(!is_regression(zero, Duration::from_millis(5), 10.0));
+    }
+
+    #[test]
+    fn test_baseline_round_trip() {
+        let filename = std::env::temp_dir().join("advent_of_code_test_baseline.txt");
+        let filename = filename.to_str().unwrap();
+        let mut baseline = Baseline::default();
+        baseline.medians.insert(3, Duration::from_nanos(1234));
+        baseline.medians.insert(11, Duration::from_millis(5));
+        baseline.save(filename).unwrap();
+        assert_eq!(baseline, Baseline::load(filename).unwrap());
+        fs::remove_file(filename).unwrap();
+    }
+
+    #[test]
+    fn test_malformed_baseline() {
+        let err = Baseline::load("input/day_eight_example.txt").unwrap_err();
+        assert_eq!(
+            "input/day_eight_example.txt:1:1: invalid number: invalid digit found in string",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_sample() {
+        let registry = Registry::default();
+        let day_one = registry.get(1).unwrap();
+        assert_eq!(3, sample(day_one, None, None, 1, 3).unwrap().len());
+        assert!(sample(day_one, Some("input/day_eight.txt"), None, 0, 1).is_err());
+    }
+}
@@ -1,102 +1,437 @@
 use ansi_term::Color;
 use ansi_term::Style;
-use std::collections::HashSet;
 use std::env;
-use std::time::Instant;
-
-use advent_of_code;
-
-const DAYS: [&dyn Fn() -> String; 25] = [
-    &advent_of_code::day_one::solve,
-    &advent_of_code::day_two::solve,
-    &advent_of_code::day_three::solve,
-    &advent_of_code::day_four::solve,
-    &advent_of_code::day_five::solve,
-    &advent_of_code::day_six::solve,
-    &advent_of_code::day_seven::solve,
-    &advent_of_code::day_eight::solve,
-    &advent_of_code::day_nine::solve,
-    &advent_of_code::day_ten::solve,
-    &advent_of_code::day_eleven::solve,
-    &advent_of_code::day_twelve::solve,
-    &advent_of_code::day_thirteen::solve,
-    &advent_of_code::day_fourteen::solve,
-    &advent_of_code::day_fifteen::solve,
-    &advent_of_code::day_sixteen::solve,
-    &advent_of_code::day_seventeen::solve,
-    &advent_of_code::day_eighteen::solve,
-    &advent_of_code::day_nineteen::solve,
-    &advent_of_code::day_twenty::solve,
-    &advent_of_code::day_twenty_one::solve,
-    &advent_of_code::day_twenty_two::solve,
-    &advent_of_code::day_twenty_three::solve,
-    &advent_of_code::christmas_eve::solve,
-    &advent_of_code::christmas_day::solve,
-];
-
-fn log_elapsed(last: &mut Instant, day: usize) {
-    let now = Instant::now();
-    let time_elapsed = now.duration_since(*last).as_millis();
-
-    let log_message;
-    if day == 24 {
-        log_message = "\ttime elapsed for christmas eve:".to_owned();
-    } else if day == 25 {
-        log_message = "\ttime elapsed for christmas day:".to_owned();
+use std::io::{self, IsTerminal};
+use std::panic;
+use std::path::Path;
+use std::process;
+use std::time::{Duration, Instant};
+
+use advent_of_code::answers::{self, Answers, Verdict};
+use advent_of_code::bench::{self, Baseline, Stats};
+use advent_of_code::cli::{self, Command, Options, Subcommand};
+use advent_of_code::report::{self, Format, Record, Status, Totals};
+use advent_of_code::scaffold;
+use advent_of_code::solver::{day_name, registry_for_year, resolve_input, DynSolver, Part};
+
+// Colour and other escape codes are only useful on a terminal, so plain
+// styles are used when stdout is redirected.
+fn style(style: Style) -> Style {
+    if io::stdout().is_terminal() {
+        style
     } else {
-        log_message = format!("\ttime elapsed for day {}:", day);
+        Style::new()
     }
-    let log_time = format!("{}ms", time_elapsed);
+}
 
-    let color: Color;
-    if time_elapsed < 150 {
-        color = Color::Green;
+fn elapsed_color(time_elapsed: Duration) -> Style {
+    let time_elapsed = time_elapsed.as_millis();
+    style(if time_elapsed < 150 {
+        Color::Green.normal()
     } else if time_elapsed < 500 {
-        color = Color::Yellow;
+        Color::Yellow.normal()
     } else {
-        color = Color::Red;
+        Color::Red.normal()
+    })
+}
+
+fn paint_elapsed(time_elapsed: Duration) -> String {
+    elapsed_color(time_elapsed)
+        .paint(report::format_duration(time_elapsed))
+        .to_string()
+}
+
+// Logs the time spent on the whole day, broken down by phase so that slow
+// parsing can be told apart from a slow part.
+fn log_elapsed(records: &[Record]) {
+    let log_message = format!(
+        "\ttime elapsed for {}:",
+        day_name(records[0].day).to_lowercase()
+    );
+    let mut phases = vec![format!("parse {}", paint_elapsed(records[0].parse_time))];
+    phases.extend(
+        records
+            .iter()
+            .map(|r| format!("{} {}", r.part, paint_elapsed(r.part_time))),
+    );
+
+    let style = style(Style::new().italic());
+    println!(
+        "{} {} ({})",
+        style.paint(log_message),
+        paint_elapsed(report::total_time(records)),
+        phases.join(", ")
+    );
+}
+
+fn print_text(records: &[Record]) -> Result<(), String> {
+    if let Some(reason) = report::first_error(records) {
+        return Err(reason.to_owned());
     }
-    let style = Style::new().italic();
-    println!("{} {}", style.paint(log_message), color.paint(log_time));
-    *last = now;
+    let answer = |r: &Record| r.answer.as_ref().map(|a| a.to_string()).unwrap_or_default();
+    let day = records[0].day;
+    let day_fragment = style(Style::new().bold()).paint(day_name(day));
+    let parts = records
+        .iter()
+        .map(|r| format!("{}: {}", r.part, answer(r)))
+        .collect::<Vec<_>>()
+        .join(", ");
+    println!("{}: {}", day_fragment, parts);
+    log_elapsed(records);
+    Ok(())
 }
 
-fn run_day(i: usize, now: &mut Instant) {
-    let style = Style::new().bold();
+// Solves the selected days. Text output stops at the first day with bad
+// input, while the machine readable formats report it and carry on.
+fn run_days(solvers: &[&dyn DynSolver], options: &Options) -> i32 {
+    if options.format == Format::Csv {
+        println!("{}", report::CSV_HEADER);
+    }
+    let mut exit_code = cli::EXIT_SUCCESS;
+    let mut totals = Totals::default();
+    let start = Instant::now();
+    report::solve_in_parallel(
+        solvers,
+        options.input.as_deref(),
+        options.part,
+        options.jobs,
+        |records| {
+            totals.add_day(&records);
+            if records.iter().any(|r| r.status != Status::Ok) {
+                exit_code = cli::EXIT_FAILURE;
+            }
+            match options.format {
+                Format::Text => {
+                    if let Err(reason) = print_text(&records) {
+                        eprintln!("error: {}", reason);
+                        return false;
+                    }
+                }
+                Format::Json => records.iter().for_each(|r| println!("{}", r.to_json())),
+                Format::Csv => records.iter().for_each(|r| println!("{}", r.to_csv())),
+            }
+            true
+        },
+    );
 
-    let day_name;
-    if i == 24 {
-        day_name = "Christmas Eve".to_owned();
-    } else if i == 25 {
-        day_name = "Christmas Day".to_owned();
-    } else {
-        day_name = format!("Day {}", i);
+    totals.wall_time = start.elapsed();
+    match options.format {
+        Format::Text if exit_code == cli::EXIT_SUCCESS && solvers.len() > 1 => println!(
+            "{} wall {}, summed day time {} with {} jobs",
+            style(Style::new().italic()).paint("total:"),
+            report::format_duration(totals.wall_time),
+            report::format_duration(totals.summed_day_time),
+            options.jobs
+        ),
+        Format::Text => (),
+        Format::Json => println!("{}", totals.to_json()),
+        Format::Csv => println!("{}", totals.to_csv()),
     }
-    let day_fragment = style.paint(day_name);
-    println!("{}: {}", day_fragment, DAYS[i - 1]());
-    log_elapsed(now, i);
+    exit_code
 }
 
-fn main() {
-    let mut run_all = false;
-    let args: HashSet<i64> = env::args()
-        .map(|a| {
-            if a == "-a" {
-                run_all = true;
+fn format_stats(stats: &Stats) -> String {
+    format!(
+        "min {}, median {}, mean {} ± {}",
+        report::format_duration(stats.min),
+        paint_elapsed(stats.median),
+        report::format_duration(stats.mean),
+        report::format_duration(stats.stddev)
+    )
+}
+
+// Runs each day repeatedly and summarizes the timings, optionally comparing
+// the medians against a saved baseline.
+fn bench_days(solvers: &[&dyn DynSolver], options: &Options) -> i32 {
+    let bench = &options.bench;
+    let baseline = match bench.baseline.as_deref().map(Baseline::load) {
+        Some(Ok(baseline)) => Some(baseline),
+        Some(Err(err)) => {
+            eprintln!("error: {}", err);
+            return cli::EXIT_FAILURE;
+        }
+        None => None,
+    };
+
+    let mut medians = Baseline::default();
+    let mut total = Duration::default();
+    let mut regressions = 0;
+    for solver in solvers {
+        let samples = match bench::sample(
+            *solver,
+            options.input.as_deref(),
+            options.part,
+            bench.warmup,
+            bench.runs,
+        ) {
+            Ok(samples) => samples,
+            Err(reason) => {
+                eprintln!("error: {}", reason);
+                return cli::EXIT_FAILURE;
             }
-            a.parse::<i64>().unwrap_or(0)
-        })
-        .filter(|a| *a > 0)
-        .collect();
-
-    let mut now = Instant::now();
-    if run_all {
-        for i in 0..DAYS.len() {
-            run_day(i + 1, &mut now);
+        };
+        let stats = Stats::from_samples(&samples);
+        total += stats.median;
+        medians.medians.insert(solver.day(), stats.median);
+
+        let mut comparison = String::new();
+        if let Some(previous) = baseline.as_ref().and_then(|b| b.medians.get(&solver.day())) {
+            let change = match bench::change_percent(*previous, stats.median) {
+                Some(change) => format!(
+                    " ({:+.1}% vs {})",
+                    change,
+                    report::format_duration(*previous)
+                ),
+                None => format!(" (vs {})", report::format_duration(*previous)),
+            };
+            if bench::is_regression(*previous, stats.median, bench.threshold_percent) {
+                regressions += 1;
+                comparison = format!(
+                    "{} {}",
+                    change,
+                    style(Color::Red.bold()).paint("REGRESSION")
+                );
+            } else {
+                comparison = change;
+            }
+        }
+        println!(
+            "{}: {}{}",
+            style(Style::new().bold()).paint(day_name(solver.day())),
+            format_stats(&stats),
+            comparison
+        );
+    }
+    println!(
+        "{} {} over {} runs per day",
+        style(Style::new().italic()).paint("total of medians:"),
+        report::format_duration(total),
+        bench.runs
+    );
+
+    if let Some(filename) = bench.save_baseline.as_deref() {
+        if let Err(err) = medians.save(filename) {
+            eprintln!("error: {}", err);
+            return cli::EXIT_FAILURE;
+        }
+    }
+    if regressions > 0 {
+        println!(
+            "{} of {} days regressed by more than {}%",
+            regressions,
+            solvers.len(),
+            bench.threshold_percent
+        );
+        return cli::EXIT_FAILURE;
+    }
+    cli::EXIT_SUCCESS
+}
+
+fn list_days(solvers: &[&dyn DynSolver]) -> i32 {
+    for solver in solvers {
+        println!("{:>2}  {}", solver.day(), solver.title());
+    }
+    cli::EXIT_SUCCESS
+}
+
+fn paint_verdict(verdict: &Verdict) -> String {
+    match verdict {
+        Verdict::Pass => style(Color::Green.normal()).paint("pass").to_string(),
+        Verdict::Fail { expected, actual } => format!(
+            "{} (expected {}, got {})",
+            style(Color::Red.normal()).paint("FAIL"),
+            expected,
+            actual
+        ),
+        Verdict::Unsolved => style(Color::Yellow.normal()).paint("unsolved").to_string(),
+    }
+}
+
+// Solves every selected day and compares each part against the answers
+// manifest, carrying on past bad input and panics.
+fn check_days(solvers: &[&dyn DynSolver], options: &Options) -> i32 {
+    let filename = options
+        .answers
+        .clone()
+        .unwrap_or_else(|| answers::default_filename(options.year));
+    let answers = match Answers::load(&filename) {
+        Ok(answers) => answers,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            return cli::EXIT_FAILURE;
         }
+    };
+
+    let (mut passed, mut failed, mut unsolved) = (0, 0, 0);
+    for solver in solvers {
+        let expected = answers.get(solver.day()).cloned().unwrap_or_default();
+        // An explicit --input wins over the one listed in the manifest.
+        let input = options.input.as_deref().or(expected.input.as_deref());
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            report::solve_timed(*solver, input, options.part)
+        }));
+
+        let status = match result {
+            Err(_) => {
+                failed += 1;
+                style(Color::Red.normal())
+                    .paint("FAIL (panicked)")
+                    .to_string()
+            }
+            Ok(records) => match report::first_error(&records) {
+                Some(reason) => {
+                    failed += 1;
+                    format!("{}: {}", style(Color::Red.normal()).paint("FAIL"), reason)
+                }
+                None => {
+                    let mut parts = Vec::new();
+                    for record in records.iter() {
+                        let verdict = answers::verify(
+                            expected.get(record.part),
+                            record.answer.as_ref().unwrap(),
+                        );
+                        match verdict {
+                            Verdict::Pass => passed += 1,
+                            Verdict::Fail { .. } => failed += 1,
+                            Verdict::Unsolved => unsolved += 1,
+                        }
+                        parts.push(format!("{}: {}", record.part, paint_verdict(&verdict)));
+                    }
+                    parts.join(", ")
+                }
+            },
+        };
+        println!(
+            "{}: {}",
+            style(Style::new().bold()).paint(day_name(solver.day())),
+            status
+        );
+    }
+
+    println!(
+        "{} passed, {} failed, {} unsolved",
+        passed, failed, unsolved
+    );
+    if failed > 0 {
+        cli::EXIT_FAILURE
     } else {
-        for arg in args.iter() {
-            run_day(*arg as usize, &mut now);
+        cli::EXIT_SUCCESS
+    }
+}
+
+// Runs the automaton of every selected day that has one with the rule and
+// number of steps from the command line, instead of the day's own.
+fn run_automata(solvers: &[&dyn DynSolver], options: &Options) -> i32 {
+    let parts = match options.part {
+        Some(part) => vec![part],
+        None => vec![Part::One, Part::Two],
+    };
+    let mut ran = 0;
+    for solver in solvers {
+        let input = match solver.parse(&resolve_input(*solver, options.input.as_deref())) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("error: {}", err);
+                return cli::EXIT_FAILURE;
+            }
+        };
+        let mut results = Vec::new();
+        for part in parts.iter() {
+            match solver.run_automaton(input.as_ref(), *part, &options.experiment) {
+                Some(Ok(answer)) => results.push(format!("{}: {}", part, answer)),
+                Some(Err(err)) => {
+                    eprintln!("error: {}", err);
+                    return cli::EXIT_FAILURE;
+                }
+                None => (),
+            }
+        }
+        if !results.is_empty() {
+            ran += 1;
+            println!(
+                "{}: {}",
+                style(Style::new().bold()).paint(day_name(solver.day())),
+                results.join(", ")
+            );
+        }
+    }
+    if ran == 0 {
+        eprintln!("error: none of the selected days run an automaton");
+        return cli::EXIT_FAILURE;
+    }
+    cli::EXIT_SUCCESS
+}
+
+// Generates the new day in the current directory, which should be the root
+// of this repository.
+fn new_day(options: &Options) -> i32 {
+    let day = options.days[0];
+    let title = options.title.as_deref().unwrap_or("Untitled");
+    match scaffold::new_day(Path::new("."), options.year, day, title) {
+        Ok(changed) => {
+            for path in changed {
+                println!("wrote {}", path.display());
+            }
+            cli::EXIT_SUCCESS
+        }
+        Err(err) => {
+            eprintln!("error: {}", err);
+            cli::EXIT_FAILURE
         }
     }
 }
+
+fn main() {
+    let options = match cli::parse_args(env::args().skip(1)) {
+        Ok(Command::Help) => {
+            println!("{}", cli::USAGE);
+            process::exit(cli::EXIT_SUCCESS);
+        }
+        Ok(Command::Execute(options)) => options,
+        Err(err) => {
+            eprintln!("error: {}", err);
+            eprintln!("run with --help for usage");
+            process::exit(cli::EXIT_USAGE);
+        }
+    };
+
+    // The new day has no solver to look up yet.
+    if options.command == Subcommand::NewDay {
+        process::exit(new_day(&options));
+    }
+
+    let registry = match registry_for_year(options.year) {
+        Some(registry) => registry,
+        None => {
+            eprintln!("error: no days of {} have been added yet", options.year);
+            process::exit(cli::EXIT_FAILURE);
+        }
+    };
+    let mut solvers = Vec::with_capacity(options.days.len());
+    for day in options.days.iter() {
+        match registry.get(*day) {
+            Some(solver) => solvers.push(solver),
+            // Years that are still being solved don't have every day.
+            None if options.all_days => (),
+            None => {
+                eprintln!("error: no solver registered for day {}", day);
+                process::exit(cli::EXIT_FAILURE);
+            }
+        }
+    }
+
+    if let Err(err) = cli::check_input(&options, &solvers) {
+        eprintln!("error: {}", err);
+        process::exit(cli::EXIT_USAGE);
+    }
+
+    let exit_code = match options.command {
+        Subcommand::Run => run_days(&solvers, &options),
+        Subcommand::Bench => bench_days(&solvers, &options),
+        Subcommand::List => list_days(&solvers),
+        Subcommand::Check => check_days(&solvers, &options),
+        Subcommand::Automaton => run_automata(&solvers, &options),
+        Subcommand::NewDay => unreachable!("handled above"),
+    };
+    process::exit(exit_code);
+}
@@ -1,3 +1,8 @@
+use crate::error::Error;
+use crate::solver::{Answer, Solver};
+
+pub struct ChristmasDay;
+
 fn part_one() -> i64 {
     0
 }
@@ -6,8 +11,28 @@ fn part_two() -> i64 {
     0
 }
 
-pub fn solve() -> String {
-    format!("part one: {}, part two: {}", part_one(), part_two())
+impl Solver for ChristmasDay {
+    type Input = ();
+
+    fn day(&self) -> u32 {
+        25
+    }
+
+    fn title(&self) -> &'static str {
+        "Combo Breaker"
+    }
+
+    fn parse(&self, _filename: &str) -> Result<Self::Input, Error> {
+        Ok(())
+    }
+
+    fn part_one(&self, _input: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one().into())
+    }
+
+    fn part_two(&self, _input: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two().into())
+    }
 }
 
 #[cfg(test)]
@@ -16,6 +41,6 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 0, part two: 0", solve());
+        assert_eq!("part one: 0, part two: 0", ChristmasDay.solve());
     }
 }
@@ -1,116 +1,50 @@
-use crate::loadable::LoadableFromFile;
-use crate::yet_another_geometry_mod::Point3D;
-use std::collections::HashMap;
+use crate::automaton::{Automaton, Experiment, Neighborhood, SparseAutomaton};
+use crate::error::Error;
+use crate::loadable::{parse_lines, LoadableFromFile};
+use crate::solver::{Answer, Part, Solver};
+use crate::yet_another_geometry_mod::{Advance, HexDirection, Point3D};
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::BufRead;
-use std::io::BufReader;
 use std::str::FromStr;
 
-// e, se, sw, w, nw, and ne
-#[derive(Debug, Copy, Clone, PartialEq)]
-enum HexDirection {
-    East,
-    SouthEast,
-    SouthWest,
-    West,
-    NorthWest,
-    NorthEast,
-}
-
-#[derive(Debug, PartialEq)]
-enum State {
-    SawNorth,
-    SawSouth,
-    Normal,
-}
-
 #[derive(Default, Debug)]
-struct Instructions {
+pub struct Instructions {
     value: Vec<HexDirection>,
 }
 
 impl FromStr for Instructions {
-    type Err = &'static str;
+    type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         let mut instructions = Instructions::default();
-        let mut state = State::Normal;
-        for c in s.chars() {
-            let mut next = HexDirection::East;
-            match c {
-                'e' => {
-                    match state {
-                        State::SawNorth => next = HexDirection::NorthEast,
-                        State::SawSouth => next = HexDirection::SouthEast,
-                        State::Normal => next = HexDirection::East,
-                    }
-                    state = State::Normal;
-                }
-                'w' => {
-                    match state {
-                        State::SawNorth => next = HexDirection::NorthWest,
-                        State::SawSouth => next = HexDirection::SouthWest,
-                        State::Normal => next = HexDirection::West,
-                    }
-                    state = State::Normal;
-                }
-                'n' => state = State::SawNorth,
-                's' => state = State::SawSouth,
-                _ => return Err("invalid character"),
-            }
-
-            if state == State::Normal {
-                instructions.value.push(next);
+        // Directions aren't separated, but only north and south ones take two
+        // characters (and there are no plain north or south directions).
+        let mut start = 0;
+        while let Some(first) = s[start..].chars().next() {
+            let mut length = first.len_utf8();
+            if first == 'n' || first == 's' {
+                let second = s[start + length..].chars().next().ok_or_else(|| {
+                    Error::parse("directions end halfway through a step").at_column(s.len())
+                })?;
+                length += second.len_utf8();
             }
+            let step = &s[start..start + length];
+            let direction = HexDirection::from_str(step).map_err(|_| {
+                let c = step.chars().last().unwrap();
+                Error::parse(format!("unexpected '{}' in directions", c))
+                    .at_column(start + length - c.len_utf8() + 1)
+            })?;
+            instructions.value.push(direction);
+            start += length;
         }
-
         Ok(instructions)
     }
 }
 
 impl LoadableFromFile for Vec<Instructions> {
-    fn load(filename: &str) -> Self {
-        let file = File::open(filename).expect("invalid filename");
-        let reader = BufReader::new(file);
-
-        reader
-            .lines()
-            .map(|l| Instructions::from_str(&l.unwrap()).unwrap())
-            .collect()
+    fn try_load_str(contents: &str) -> Result<Self, Error> {
+        parse_lines(contents, Instructions::from_str)
     }
 }
 
-fn advance(loc: &Point3D, dir: &HexDirection) -> Point3D {
-    let mut location = *loc;
-    match dir {
-        HexDirection::East => {
-            location.x += 1;
-            location.y -= 1;
-        }
-        HexDirection::West => {
-            location.x -= 1;
-            location.y += 1;
-        }
-        HexDirection::NorthEast => {
-            location.z -= 1;
-            location.x += 1;
-        }
-        HexDirection::NorthWest => {
-            location.z -= 1;
-            location.y += 1;
-        }
-        HexDirection::SouthEast => {
-            location.y -= 1;
-            location.z += 1;
-        }
-        HexDirection::SouthWest => {
-            location.x -= 1;
-            location.z += 1;
-        }
-    }
-    location
-}
-
 // We can traverse using a hexagonal grid based on 3D coordinates:
 // https://www.redblobgames.com/grids/hexagons/#coordinates
 fn part_one(instructions_list: &[Instructions]) -> HashSet<Point3D> {
@@ -118,8 +52,7 @@ fn part_one(instructions_list: &[Instructions]) -> HashSet<Point3D> {
     for instructions in instructions_list.iter() {
         let mut cur = Point3D::default();
         for direction in instructions.value.iter() {
-            let next = advance(&cur, direction);
-            cur = next;
+            cur.advance(*direction);
         }
         if flipped_black.contains(&cur) {
             flipped_black.remove(&cur);
@@ -130,67 +63,68 @@ fn part_one(instructions_list: &[Instructions]) -> HashSet<Point3D> {
     flipped_black
 }
 
-const NEIGHBOR_OFFSETS_HEX: [HexDirection; 6] = [
-    HexDirection::East,
-    HexDirection::NorthEast,
-    HexDirection::NorthWest,
-    HexDirection::West,
-    HexDirection::SouthWest,
-    HexDirection::SouthEast,
-];
-
-// TODO: refactor to share from day 17.
-fn run_iteration(
-    lifeforms: &HashSet<Point3D>,
-    neighbor_offsets: &[HexDirection],
-) -> HashSet<Point3D> {
-    let mut possible_life = HashMap::<Point3D, usize>::new();
-    for l in lifeforms.iter() {
-        for d in neighbor_offsets.iter() {
-            let point = advance(&l, &d);
-
-            let entry;
-            if lifeforms.contains(&point) {
-                // Neighbor is already alive, so modify this life-form. The
-                // neighbor will be updated with this life form later.
-                entry = possible_life.entry(*l).or_insert(0);
-            } else {
-                // Neighbor is not alive yet, so doesn't impact this life-form,
-                // but may become alive itself.
-                entry = possible_life.entry(point).or_insert(0);
-            }
-            *entry += 1;
-        }
-    }
+// If a tile is white, it will flip to black if it has two black neighbors.
+// If a tile is black, this is also true (so color doesn't matter), but it
+// also stays black if it has 1 black neighbor.
+const RULE: &str = "B2/S12";
+const DAYS: usize = 100;
 
-    possible_life
-        .into_iter()
-        // If a tile is white, it will flip to black if it has two
-        // black neighbors.
-        // If a tile is black, this is also true (so color doesn't matter),
-        // but it also stays black if it has 1 black neighbor.
-        .filter(|kv| kv.1 == 2 || (kv.1 == 1 && lifeforms.contains(&kv.0)))
-        .map(|kv| kv.0)
-        .collect()
+// Part two is basically Conways' Game of Life again.
+fn simulate(flipped_day_one: &HashSet<Point3D>, experiment: &Experiment) -> Result<i64, Error> {
+    let mut flipped = SparseAutomaton::new(
+        flipped_day_one.iter().copied(),
+        Neighborhood::hex(),
+        experiment.rule_or(RULE),
+    )?;
+    flipped.step_n(experiment.steps_or(DAYS));
+    Ok(flipped.population() as i64)
 }
 
-// Part two is basically Conways' Game of Life again.
 fn part_two(flipped_day_one: &HashSet<Point3D>) -> i64 {
-    let mut flipped: HashSet<Point3D> = flipped_day_one.clone();
-    for _ in 1..101 {
-        flipped = run_iteration(&flipped, &NEIGHBOR_OFFSETS_HEX);
-    }
-    flipped.len() as i64
+    simulate(flipped_day_one, &Experiment::default()).unwrap()
 }
 
-pub fn solve() -> String {
-    let instructions_list = Vec::<Instructions>::load("input/day_twentyfour.txt");
-    let flipped_day_one = part_one(&instructions_list);
-    format!(
-        "part one: {}, part two: {}",
-        flipped_day_one.len(),
-        part_two(&flipped_day_one)
-    )
+pub struct ChristmasEve;
+
+impl Solver for ChristmasEve {
+    type Input = Vec<Instructions>;
+
+    fn day(&self) -> u32 {
+        24
+    }
+
+    fn title(&self) -> &'static str {
+        "Lobby Layout"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some("input/day_twentyfour.txt")
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Vec::<Instructions>::try_load(filename)
+    }
+
+    fn part_one(&self, instructions_list: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(instructions_list).len().into())
+    }
+
+    fn part_two(&self, instructions_list: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(&part_one(instructions_list)).into())
+    }
+
+    // Part one only follows the directions.
+    fn run_automaton(
+        &self,
+        instructions_list: &Self::Input,
+        part: Part,
+        experiment: &Experiment,
+    ) -> Option<Result<Answer, Error>> {
+        match part {
+            Part::One => None,
+            Part::Two => Some(simulate(&part_one(instructions_list), experiment).map(Answer::from)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -199,7 +133,7 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 465, part two: 4078", solve());
+        assert_eq!("part one: 465, part two: 4078", ChristmasEve.solve());
     }
 
     #[test]
@@ -210,4 +144,55 @@ mod tests {
         assert_eq!(10, flipped_day_one.len());
         assert_eq!(2208, part_two(&flipped_day_one));
     }
+
+    #[test]
+    fn test_experiment() {
+        let il = Vec::<Instructions>::load("input/day_twentyfour_example.txt");
+        let first_day = Experiment {
+            rule: None,
+            steps: Some(1),
+        };
+        assert!(ChristmasEve
+            .run_automaton(&il, Part::One, &first_day)
+            .is_none());
+        assert_eq!(
+            Answer::Number(15),
+            ChristmasEve
+                .run_automaton(&il, Part::Two, &first_day)
+                .unwrap()
+                .unwrap()
+        );
+        let from_nothing = Experiment {
+            rule: Some("B0/S".parse().unwrap()),
+            steps: None,
+        };
+        assert!(matches!(
+            ChristmasEve.run_automaton(&il, Part::Two, &from_nothing),
+            Some(Err(_))
+        ));
+    }
+
+    #[test]
+    fn test_directions() {
+        assert_eq!(
+            vec![
+                HexDirection::East,
+                HexDirection::SouthEast,
+                HexDirection::NorthWest,
+                HexDirection::West
+            ],
+            Instructions::from_str("esenww").unwrap().value
+        );
+        let error = |s| Instructions::from_str(s).unwrap_err().to_string();
+        assert_eq!("column 4: unexpected 'x' in directions", error("esex"));
+        assert_eq!("column 2: unexpected 'n' in directions", error("nne"));
+        assert_eq!("column 3: unexpected 's' in directions", error("ens"));
+        assert_eq!(
+            "column 2: directions end halfway through a step",
+            error("en")
+        );
+        assert_eq!("column 2: unexpected 'é' in directions", error("eé"));
+        assert_eq!("column 1: unexpected 'E' in directions", error("Ese"));
+        assert_eq!("column 2: unexpected 'W' in directions", error("nWe"));
+    }
 }
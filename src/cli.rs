@@ -0,0 +1,655 @@
+use crate::automaton::{Experiment, Rule};
+use crate::bench::{DEFAULT_RUNS, DEFAULT_THRESHOLD_PERCENT, DEFAULT_WARMUP};
+use crate::report::Format;
+use crate::solver::{DynSolver, Part, DEFAULT_YEAR, FIRST_DAY, LAST_DAY, STDIN_FILENAME};
+use std::collections::BTreeSet;
+use std::fmt;
+
+// Exit codes used by the binary. Usage errors match the common convention
+// of argument parsers (e.g. getopt based tools) returning 2.
+pub const EXIT_SUCCESS: i32 = 0;
+pub const EXIT_FAILURE: i32 = 1;
+pub const EXIT_USAGE: i32 = 2;
+
+pub const USAGE: &str = "\
+Advent of Code solutions, 2020 unless another year is picked.
+
+USAGE:
+    advent_of_code [COMMAND] [OPTIONS] [DAYS...]
+    advent_of_code new-day [--year <YEAR>] <DAY> [TITLE]
+
+COMMANDS:
+    run      Solve the selected days (default)
+    bench    Time the selected days
+    list     List the available days
+    check    Solve the selected days and verify the answers against
+             answers.toml
+    automaton
+             Run the cellular automaton of the selected days with
+             another rule or number of steps
+    new-day  Generate, declare and register a new day from the day
+             template, along with empty inputs and an answers entry.
+             Days of other years than 2020 go in src/year_<YEAR>, with
+             inputs in input/<YEAR> and answers in answers_<YEAR>.toml
+
+DAYS:
+    Day numbers (7), inclusive ranges (3-7) or names (christmas-eve,
+    christmas-day). The bench, list and check commands default to every
+    day when none are given.
+
+OPTIONS:
+    -a, --all             Select every day
+    -y, --year <YEAR>     Pick the days of YEAR (default 2020)
+    -p, --part <1|2>      Only solve one part
+    -i, --input <PATH>    Read the puzzle input from PATH instead of the
+                          bundled input, or from stdin if PATH is -.
+                          PATH may be gzip compressed if it ends in .gz.
+                          Requires exactly one day, which has to read an
+                          input. Bench reads the input again on every run,
+                          so it can't read stdin.
+    -j, --jobs <N>        Solve up to N days of the run command at once
+                          (default 1). Results are still printed in day
+                          order.
+    --format <FORMAT>     Output format of the run command: text
+                          (default), json (one object per line) or csv.
+                          Both end with the wall time of the run and the
+                          time of every day added up. Colour is only used
+                          when writing to a terminal.
+    --answers <PATH>      Answers manifest used by the check command
+                          (default answers.toml, or answers_<YEAR>.toml
+                          for other years)
+    -h, --help            Print this message
+
+BENCH OPTIONS:
+    --runs <N>            Measured runs per day (default 10)
+    --warmup <N>          Unmeasured runs per day before measuring
+                          (default 2)
+    --save-baseline <PATH>
+                          Save the median time of each day to PATH
+    --baseline <PATH>     Compare against medians saved to PATH and fail
+                          on regressions
+    --threshold <PERCENT> How much slower than the baseline a day may
+                          get before it counts as a regression (default 10)
+
+AUTOMATON OPTIONS:
+    --rule <RULE>         Birth and survival rule, e.g. B3/S23, or B2/S/C3
+                          for a rule with 3 states (default: the day's own)
+    --steps <N>           Number of steps to run (default: the day's own)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Subcommand {
+    Run,
+    Bench,
+    List,
+    Check,
+    NewDay,
+    Automaton,
+}
+
+// Only used by the bench command.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BenchOptions {
+    pub runs: usize,
+    pub warmup: usize,
+    pub baseline: Option<String>,
+    pub save_baseline: Option<String>,
+    pub threshold_percent: f64,
+}
+
+impl Default for BenchOptions {
+    fn default() -> Self {
+        BenchOptions {
+            runs: DEFAULT_RUNS,
+            warmup: DEFAULT_WARMUP,
+            baseline: None,
+            save_baseline: None,
+            threshold_percent: DEFAULT_THRESHOLD_PERCENT,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Options {
+    pub command: Subcommand,
+    // Sorted and deduplicated.
+    pub days: Vec<u32>,
+    // Whether |days| is the whole calendar because no day was picked, so
+    // days of the year that aren't solved yet can be skipped.
+    pub all_days: bool,
+    pub part: Option<Part>,
+    pub input: Option<String>,
+    pub format: Format,
+    // Number of days the run command may solve at the same time.
+    pub jobs: usize,
+    // Answers manifest for the check command, the bundled one if None.
+    pub answers: Option<String>,
+    pub bench: BenchOptions,
+    // Every day selected belongs to this year.
+    pub year: u32,
+    // Puzzle title for the new-day command.
+    pub title: Option<String>,
+    // Only used by the automaton command.
+    pub experiment: Experiment,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Help,
+    // Boxed, since options are much bigger than nothing.
+    Execute(Box<Options>),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UsageError {
+    pub reason: String,
+}
+
+impl UsageError {
+    fn new(reason: String) -> Self {
+        UsageError { reason }
+    }
+}
+
+impl fmt::Display for UsageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+fn parse_day(s: &str) -> Result<u32, UsageError> {
+    let day = match s {
+        "christmas-eve" => 24,
+        "christmas-day" => 25,
+        _ => s
+            .parse::<u32>()
+            .map_err(|_| UsageError::new(format!("invalid day '{}'", s)))?,
+    };
+    if !(FIRST_DAY..=LAST_DAY).contains(&day) {
+        return Err(UsageError::new(format!(
+            "day {} is out of range, expected {}-{}",
+            day, FIRST_DAY, LAST_DAY
+        )));
+    }
+    Ok(day)
+}
+
+// Accepts a single day or an inclusive range of days, e.g. "3-7".
+pub fn parse_days(s: &str) -> Result<Vec<u32>, UsageError> {
+    // Named days contain dashes too, so only treat this as a range if both
+    // sides look like day numbers.
+    if let Some((first, last)) = s.split_once('-') {
+        if !first.is_empty() && first.bytes().all(|b| b.is_ascii_digit()) {
+            let first = parse_day(first)?;
+            let last = parse_day(last)?;
+            if first > last {
+                return Err(UsageError::new(format!("range '{}' is backwards", s)));
+            }
+            return Ok((first..=last).collect());
+        }
+    }
+    parse_day(s).map(|day| vec![day])
+}
+
+// The first Advent of Code was in 2015.
+const FIRST_YEAR: u32 = 2015;
+
+fn parse_year(s: &str) -> Result<u32, UsageError> {
+    match s.parse::<u32>() {
+        Ok(year) if year >= FIRST_YEAR => Ok(year),
+        _ => Err(UsageError::new(format!(
+            "invalid year '{}', expected {} or later",
+            s, FIRST_YEAR
+        ))),
+    }
+}
+
+fn parse_format(s: &str) -> Result<Format, UsageError> {
+    Format::parse(s).ok_or_else(|| {
+        UsageError::new(format!(
+            "invalid format '{}', expected text, json or csv",
+            s
+        ))
+    })
+}
+
+fn parse_count(flag: &str, s: &str, min: usize) -> Result<usize, UsageError> {
+    match s.parse::<usize>() {
+        Ok(n) if n >= min => Ok(n),
+        _ => Err(UsageError::new(format!(
+            "invalid value '{}' for {}, expected a number of at least {}",
+            s, flag, min
+        ))),
+    }
+}
+
+fn parse_percent(flag: &str, s: &str) -> Result<f64, UsageError> {
+    match s.trim_end_matches('%').parse::<f64>() {
+        Ok(p) if p.is_finite() && p >= 0.0 => Ok(p),
+        _ => Err(UsageError::new(format!(
+            "invalid value '{}' for {}, expected a percentage",
+            s, flag
+        ))),
+    }
+}
+
+fn parse_rule(s: &str) -> Result<Rule, UsageError> {
+    s.parse::<Rule>()
+        .map_err(|err| UsageError::new(format!("invalid rule '{}', {}", s, err)))
+}
+
+fn parse_part(s: &str) -> Result<Part, UsageError> {
+    match s {
+        "1" => Ok(Part::One),
+        "2" => Ok(Part::Two),
+        _ => Err(UsageError::new(format!(
+            "invalid part '{}', expected 1 or 2",
+            s
+        ))),
+    }
+}
+
+// The new-day command takes exactly one day and an optional title, which
+// may contain anything (including dashes and numbers), and optionally the
+// year before them.
+fn parse_new_day_args<I>(args: I) -> Result<Command, UsageError>
+where
+    I: Iterator<Item = String>,
+{
+    let mut args: Vec<String> = args.collect();
+    if args.iter().any(|a| a == "-h" || a == "--help") {
+        return Ok(Command::Help);
+    }
+    let mut year = DEFAULT_YEAR;
+    if let Some(flag) = args.first().filter(|a| *a == "-y" || *a == "--year") {
+        let value = args
+            .get(1)
+            .ok_or_else(|| UsageError::new(format!("{} requires a value", flag)))?;
+        year = parse_year(value)?;
+        args.drain(..2);
+    }
+    let (day, title) = match args.as_slice() {
+        [day] => (day, None),
+        [day, title] => (day, Some(title.clone())),
+        _ => {
+            return Err(UsageError::new(
+                "new-day expects a single day and an optional title".to_owned(),
+            ))
+        }
+    };
+    Ok(Command::Execute(Box::new(Options {
+        command: Subcommand::NewDay,
+        days: vec![parse_day(day)?],
+        all_days: false,
+        part: None,
+        input: None,
+        format: Format::Text,
+        jobs: 1,
+        answers: None,
+        bench: BenchOptions::default(),
+        year,
+        title,
+        experiment: Experiment::default(),
+    })))
+}
+
+// Parses the arguments, *not* including the program name.
+pub fn parse_args<I>(args: I) -> Result<Command, UsageError>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut args = args.into_iter().peekable();
+    let command = match args.peek().map(|a| a.as_str()) {
+        Some("run") => Some(Subcommand::Run),
+        Some("bench") => Some(Subcommand::Bench),
+        Some("list") => Some(Subcommand::List),
+        Some("check") => Some(Subcommand::Check),
+        Some("new-day") => Some(Subcommand::NewDay),
+        Some("automaton") => Some(Subcommand::Automaton),
+        _ => None,
+    };
+    if command.is_some() {
+        args.next();
+    }
+    if command == Some(Subcommand::NewDay) {
+        return parse_new_day_args(args);
+    }
+    let command = command.unwrap_or(Subcommand::Run);
+
+    let mut all = false;
+    let mut days = BTreeSet::new();
+    let mut part = None;
+    let mut input = None;
+    let mut format = None;
+    let mut jobs = None;
+    let mut answers = None;
+    let mut year = DEFAULT_YEAR;
+    let mut bench = BenchOptions::default();
+    // The first bench only flag that was passed, if any.
+    let mut bench_flag = None;
+    let mut experiment = Experiment::default();
+    let mut automaton_flag = None;
+    while let Some(arg) = args.next() {
+        let mut value_for = |flag: &str| {
+            args.next()
+                .ok_or_else(|| UsageError::new(format!("{} requires a value", flag)))
+        };
+        if [
+            "--runs",
+            "--warmup",
+            "--baseline",
+            "--save-baseline",
+            "--threshold",
+        ]
+        .contains(&arg.as_str())
+        {
+            bench_flag.get_or_insert_with(|| arg.clone());
+        }
+        if ["--rule", "--steps"].contains(&arg.as_str()) {
+            automaton_flag.get_or_insert_with(|| arg.clone());
+        }
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "-a" | "--all" => all = true,
+            "-y" | "--year" => year = parse_year(&value_for(&arg)?)?,
+            "-p" | "--part" => part = Some(parse_part(&value_for(&arg)?)?),
+            "-i" | "--input" => input = Some(value_for(&arg)?),
+            "--format" => format = Some(parse_format(&value_for(&arg)?)?),
+            "--answers" => answers = Some(value_for(&arg)?),
+            "-j" | "--jobs" => jobs = Some(parse_count(&arg, &value_for(&arg)?, 1)?),
+            "--runs" => bench.runs = parse_count(&arg, &value_for(&arg)?, 1)?,
+            "--warmup" => bench.warmup = parse_count(&arg, &value_for(&arg)?, 0)?,
+            "--baseline" => bench.baseline = Some(value_for(&arg)?),
+            "--save-baseline" => bench.save_baseline = Some(value_for(&arg)?),
+            "--threshold" => bench.threshold_percent = parse_percent(&arg, &value_for(&arg)?)?,
+            "--rule" => experiment.rule = Some(parse_rule(&value_for(&arg)?)?),
+            "--steps" => experiment.steps = Some(parse_count(&arg, &value_for(&arg)?, 0)?),
+            // A lone dash is not a flag, but it isn't a day either.
+            a if a.starts_with('-') && a.len() > 1 && !a.as_bytes()[1].is_ascii_digit() => {
+                return Err(UsageError::new(format!("unknown option '{}'", a)));
+            }
+            a => days.extend(parse_days(a)?),
+        }
+    }
+
+    // Like run, the automaton command takes long enough to want days picked.
+    let needs_days = [Subcommand::Run, Subcommand::Automaton].contains(&command);
+    let all_days = all || (days.is_empty() && !needs_days);
+    if all_days {
+        days.extend(FIRST_DAY..=LAST_DAY);
+    }
+    if days.is_empty() {
+        return Err(UsageError::new(
+            "no days selected, pass day numbers or --all".to_owned(),
+        ));
+    }
+    if input.is_some() && days.len() > 1 {
+        return Err(UsageError::new(
+            "--input can only be used with a single day".to_owned(),
+        ));
+    }
+
+    if command != Subcommand::Run {
+        if format.is_some() {
+            return Err(UsageError::new(
+                "--format can only be used with the run command".to_owned(),
+            ));
+        }
+        if jobs.is_some() {
+            return Err(UsageError::new(
+                "--jobs can only be used with the run command".to_owned(),
+            ));
+        }
+    }
+
+    // Every bench run parses the input again, and stdin can only be read
+    // once.
+    if command == Subcommand::Bench && input.as_deref() == Some(STDIN_FILENAME) {
+        return Err(UsageError::new(
+            "the bench command can't read the input from stdin".to_owned(),
+        ));
+    }
+
+    if answers.is_some() && command != Subcommand::Check {
+        return Err(UsageError::new(
+            "--answers can only be used with the check command".to_owned(),
+        ));
+    }
+    if let Some(flag) = bench_flag {
+        if command != Subcommand::Bench {
+            return Err(UsageError::new(format!(
+                "{} can only be used with the bench command",
+                flag
+            )));
+        }
+    }
+    if let Some(flag) = automaton_flag {
+        if command != Subcommand::Automaton {
+            return Err(UsageError::new(format!(
+                "{} can only be used with the automaton command",
+                flag
+            )));
+        }
+    }
+
+    Ok(Command::Execute(Box::new(Options {
+        command,
+        days: days.into_iter().collect(),
+        all_days,
+        part,
+        input,
+        format: format.unwrap_or(Format::Text),
+        jobs: jobs.unwrap_or(1),
+        answers,
+        bench,
+        year,
+        title: None,
+        experiment,
+    })))
+}
+
+// Days without a bundled input don't read any input either, so an explicit
+// --input for them would silently be ignored.
+pub fn check_input(options: &Options, solvers: &[&dyn DynSolver]) -> Result<(), UsageError> {
+    if options.input.is_none() {
+        return Ok(());
+    }
+    match solvers.iter().find(|s| s.default_input().is_none()) {
+        Some(solver) => Err(UsageError::new(format!(
+            "day {} doesn't read any input, so --input can't be used",
+            solver.day()
+        ))),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::solver::registry_for_year;
+
+    fn parse(args: &[&str]) -> Result<Command, UsageError> {
+        parse_args(args.iter().map(|a| a.to_string()))
+    }
+
+    fn options(args: &[&str]) -> Options {
+        match parse(args).unwrap() {
+            Command::Execute(options) => *options,
+            Command::Help => panic!("unexpected help"),
+        }
+    }
+
+    #[test]
+    fn test_days_and_ranges() {
+        assert_eq!(vec![3, 4, 5, 6, 7], parse_days("3-7").unwrap());
+        assert_eq!(vec![24], parse_days("christmas-eve").unwrap());
+        assert_eq!(vec![25], parse_days("christmas-day").unwrap());
+        assert!(parse_days("7-3").is_err());
+        assert!(parse_days("26").is_err());
+        assert!(parse_days("0").is_err());
+        assert!(parse_days("boxing-day").is_err());
+    }
+
+    #[test]
+    fn test_default_command_is_run() {
+        let o = options(&["9", "3-4", "christmas-day", "3"]);
+        assert_eq!(Subcommand::Run, o.command);
+        assert_eq!(vec![3, 4, 9, 25], o.days);
+        assert_eq!(None, o.part);
+        assert_eq!(None, o.input);
+        assert_eq!(Format::Text, o.format);
+        assert_eq!(1, o.jobs);
+    }
+
+    #[test]
+    fn test_subcommands() {
+        assert_eq!(Subcommand::Bench, options(&["bench", "1"]).command);
+        assert_eq!(Subcommand::Check, options(&["check"]).command);
+        assert_eq!(
+            Some("mine.toml".to_owned()),
+            options(&["check", "--answers", "mine.toml"]).answers
+        );
+
+        let list = options(&["list"]);
+        assert_eq!(Subcommand::List, list.command);
+        assert_eq!(25, list.days.len());
+    }
+
+    #[test]
+    fn test_options() {
+        let o = options(&["run", "--part", "2", "-i", "-", "8"]);
+        assert_eq!(Some(Part::Two), o.part);
+        assert_eq!(Some("-".to_owned()), o.input);
+        assert_eq!(vec![8], o.days);
+        assert_eq!(25, options(&["-a"]).days.len());
+        assert_eq!(Command::Help, parse(&["run", "--help"]).unwrap());
+        assert_eq!(Format::Json, options(&["--format", "json", "1"]).format);
+        assert_eq!(Format::Csv, options(&["-a", "--format", "csv"]).format);
+        assert_eq!(4, options(&["-a", "-j", "4"]).jobs);
+    }
+
+    #[test]
+    fn test_bench_options() {
+        assert_eq!(BenchOptions::default(), options(&["bench"]).bench);
+        let o = options(&[
+            "bench",
+            "--runs",
+            "20",
+            "--warmup",
+            "0",
+            "--baseline",
+            "old.txt",
+            "--save-baseline",
+            "new.txt",
+            "--threshold",
+            "5%",
+        ]);
+        assert_eq!(
+            BenchOptions {
+                runs: 20,
+                warmup: 0,
+                baseline: Some("old.txt".to_owned()),
+                save_baseline: Some("new.txt".to_owned()),
+                threshold_percent: 5.0,
+            },
+            o.bench
+        );
+        assert_eq!(
+            UsageError::new("the bench command can't read the input from stdin".to_owned()),
+            parse(&["bench", "1", "-i", "-"]).unwrap_err()
+        );
+        assert!(parse(&["bench", "1", "-i", "input.txt"]).is_ok());
+        assert!(parse(&["run", "1", "-i", "-"]).is_ok());
+    }
+
+    #[test]
+    fn test_automaton_options() {
+        let o = options(&["automaton", "17", "--rule", "B36/S23", "--steps", "3"]);
+        assert_eq!(Subcommand::Automaton, o.command);
+        assert_eq!(
+            Experiment {
+                rule: Some(Rule::new(&[3, 6], &[2, 3])),
+                steps: Some(3),
+            },
+            o.experiment
+        );
+        assert_eq!(
+            Experiment::default(),
+            options(&["automaton", "11"]).experiment
+        );
+        assert!(parse(&["automaton"]).is_err());
+        assert_eq!(
+            UsageError::new("invalid rule 'B3/S2x', column 6: unexpected 'x' in rule".to_owned()),
+            parse(&["automaton", "17", "--rule", "B3/S2x"]).unwrap_err()
+        );
+        assert!(parse(&["run", "17", "--rule", "B3/S23"]).is_err());
+        assert!(parse(&["bench", "--steps", "3"]).is_err());
+    }
+
+    #[test]
+    fn test_new_day() {
+        let o = options(&["new-day", "12", "Rain Risk"]);
+        assert_eq!(Subcommand::NewDay, o.command);
+        assert_eq!(vec![12], o.days);
+        assert_eq!(Some("Rain Risk".to_owned()), o.title);
+        assert_eq!(None, options(&["new-day", "christmas-eve"]).title);
+        assert!(parse(&["new-day"]).is_err());
+        assert!(parse(&["new-day", "3-4"]).is_err());
+        assert!(parse(&["new-day", "3", "Title", "extra"]).is_err());
+
+        assert_eq!(DEFAULT_YEAR, o.year);
+        let o = options(&["new-day", "--year", "2021", "1", "Sonar Sweep"]);
+        assert_eq!((2021, vec![1]), (o.year, o.days));
+        assert_eq!(Some("Sonar Sweep".to_owned()), o.title);
+        assert!(parse(&["new-day", "--year"]).is_err());
+        assert!(parse(&["new-day", "--year", "1999", "1"]).is_err());
+    }
+
+    #[test]
+    fn test_year() {
+        assert_eq!(DEFAULT_YEAR, options(&["1"]).year);
+        assert_eq!(2021, options(&["list", "-y", "2021"]).year);
+        assert_eq!(2021, options(&["check", "--year", "2021", "3"]).year);
+        assert!(options(&["list", "-y", "2021"]).all_days);
+        assert!(options(&["-a"]).all_days);
+        assert!(!options(&["1-25"]).all_days);
+        assert_eq!(
+            UsageError::new("invalid year '21', expected 2015 or later".to_owned()),
+            parse(&["--year", "21", "1"]).unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_usage_errors() {
+        assert!(parse(&[]).is_err());
+        assert!(parse(&["run"]).is_err());
+        assert!(parse(&["--bogus", "1"]).is_err());
+        assert!(parse(&["--part", "3", "1"]).is_err());
+        assert!(parse(&["--part"]).is_err());
+        assert!(parse(&["1", "2", "--input", "foo.txt"]).is_err());
+        assert!(parse(&["-a", "--input", "foo.txt"]).is_err());
+        assert!(parse(&["26"]).is_err());
+        assert!(parse(&["--format", "xml", "1"]).is_err());
+        assert!(parse(&["bench", "--format", "json"]).is_err());
+        assert!(parse(&["run", "--runs", "3", "1"]).is_err());
+        assert!(parse(&["-a", "--jobs", "0"]).is_err());
+        assert!(parse(&["check", "--jobs", "2"]).is_err());
+        assert!(parse(&["run", "--answers", "a.toml", "1"]).is_err());
+        assert!(parse(&["bench", "--runs", "0"]).is_err());
+        assert!(parse(&["bench", "--threshold", "-5"]).is_err());
+    }
+
+    #[test]
+    fn test_input_of_days_without_input() {
+        let registry = registry_for_year(DEFAULT_YEAR).unwrap();
+        let day = |day| vec![registry.get(day).unwrap()];
+
+        assert!(check_input(&options(&["23", "-i", "cups.txt"]), &day(23)).is_ok());
+        assert!(check_input(&options(&["25"]), &day(25)).is_ok());
+        assert_eq!(
+            "day 25 doesn't read any input, so --input can't be used",
+            check_input(&options(&["25", "-i", "foo.txt"]), &day(25))
+                .unwrap_err()
+                .to_string()
+        );
+    }
+}
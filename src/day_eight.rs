@@ -1,8 +1,7 @@
-use crate::loadable::LoadableFromFile;
+use crate::error::Error;
+use crate::loadable::{parse_lines, LoadableFromFile};
+use crate::solver::{Answer, Solver};
 use bitvec::prelude::*;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
 use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy)]
@@ -20,6 +19,7 @@ enum State {
     Running,
 }
 
+#[derive(Clone)]
 pub struct Program {
     instructions: Vec<Instruction>,
     visited: BitVec,
@@ -39,9 +39,12 @@ trait Resettable {
 }
 
 impl FromStr for Instruction {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() < 5 || !s.is_char_boundary(3) {
+            return Err(Error::parse("expected an instruction like \"nop +0\""));
+        }
         let name_and_value = s.split_at(3);
         // Rust does not approve of leading '+' on integers.
         let value: i64 = name_and_value
@@ -49,34 +52,28 @@ impl FromStr for Instruction {
             .trim_start()
             .trim_start_matches('+')
             .parse()
-            .unwrap();
+            .map_err(|e| Error::from(e).at_column(5))?;
         match name_and_value.0 {
             "nop" => Ok(Instruction::NoOp(value)),
             "acc" => Ok(Instruction::Accumulate(value)),
             "jmp" => Ok(Instruction::Jump(value)),
-            _ => Err("invalid or unknown instruction"),
+            name => Err(Error::parse(format!("unknown instruction '{}'", name)).at_column(1)),
         }
     }
 }
 
 impl LoadableFromFile for Program {
-    fn load(filename: &str) -> Program {
-        let file = File::open(filename).expect("invalid filename");
-        let reader = BufReader::new(file);
-
-        let instructions: Vec<Instruction> = reader
-            .lines()
-            .map(|l| Instruction::from_str(&l.unwrap()).expect("invalid instruction"))
-            .collect();
+    fn try_load_str(contents: &str) -> Result<Program, Error> {
+        let instructions = parse_lines(contents, Instruction::from_str)?;
         let instruction_count = instructions.len();
 
-        Program {
-            instructions: instructions,
+        Ok(Program {
+            instructions,
             visited: bitvec![0; instruction_count],
             index: 0,
             accumulator: 0,
             state: State::Running,
-        }
+        })
     }
 }
 
@@ -126,12 +123,11 @@ fn part_one(program: &mut Program) -> i64 {
 fn part_two(program: &mut Program) -> i64 {
     for enumerable in program.instructions.clone().iter().enumerate() {
         let original = *enumerable.1;
-        let next;
-        match original {
+        let next = match original {
             Instruction::Accumulate(_) => continue,
-            Instruction::NoOp(val) => next = Instruction::Jump(val),
-            Instruction::Jump(val) => next = Instruction::NoOp(val),
-        }
+            Instruction::NoOp(val) => Instruction::Jump(val),
+            Instruction::Jump(val) => Instruction::NoOp(val),
+        };
 
         program.instructions[enumerable.0] = next;
         let accumulator_value = part_one(program);
@@ -147,13 +143,34 @@ fn part_two(program: &mut Program) -> i64 {
     program.accumulator
 }
 
-pub fn solve() -> String {
-    let mut program = Program::load("input/day_eight.txt");
-    format!(
-        "part one: {}, part two: {}",
-        part_one(&mut program),
-        part_two(&mut program)
-    )
+pub struct DayEight;
+
+impl Solver for DayEight {
+    type Input = Program;
+
+    fn day(&self) -> u32 {
+        8
+    }
+
+    fn title(&self) -> &'static str {
+        "Handheld Halting"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some("input/day_eight.txt")
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Program::try_load(filename)
+    }
+
+    fn part_one(&self, program: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(&mut program.clone()).into())
+    }
+
+    fn part_two(&self, program: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(&mut program.clone()).into())
+    }
 }
 
 #[cfg(test)]
@@ -162,7 +179,7 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 1814, part two: 1056", solve());
+        assert_eq!("part one: 1814, part two: 1056", DayEight.solve());
     }
 
     #[test]
@@ -170,4 +187,14 @@ mod tests {
         let mut program = Program::load("input/day_eight_example.txt");
         assert_eq!(5, part_one(&mut program));
     }
+
+    #[test]
+    fn test_invalid_instructions() {
+        assert_eq!(
+            "column 1: unknown instruction 'hop'",
+            Instruction::from_str("hop +1").unwrap_err().to_string()
+        );
+        assert!(Instruction::from_str("jmp +").is_err());
+        assert!(Instruction::from_str("acc").is_err());
+    }
 }
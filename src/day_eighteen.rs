@@ -1,8 +1,12 @@
-use crate::loadable::LoadableFromFile;
+use crate::error::Error;
+use crate::loadable::load_lines;
+use crate::solver::{Answer, Solver};
+use std::convert::TryFrom;
 use std::fmt;
 
-#[derive(Clone, Copy, PartialEq)]
+#[derive(Clone, Copy, Default, PartialEq)]
 enum Token {
+    #[default]
     None,
     Add,
     Multiply,
@@ -24,23 +28,41 @@ impl fmt::Debug for Token {
     }
 }
 
-impl Default for Token {
-    fn default() -> Self {
-        Token::None
-    }
-}
-
-impl From<char> for Token {
-    fn from(c: char) -> Self {
-        match c {
+impl TryFrom<char> for Token {
+    type Error = Error;
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        Ok(match c {
             '(' => Token::StartExpression,
             ')' => Token::EndExpression,
             '*' => Token::Multiply,
             '+' => Token::Add,
             ' ' => Token::None,
-            n => Token::Number(n.to_digit(10).unwrap().into()),
+            n => match n.to_digit(10) {
+                Some(d) => Token::Number(d.into()),
+                None => return Err(Error::parse(format!("unexpected '{}' in expression", n))),
+            },
+        })
+    }
+}
+
+// Checks that every character is a token and that parentheses balance, so
+// that the solvers below can assume well formed expressions.
+fn validate(expression: &str) -> Result<String, Error> {
+    let mut depth = 0;
+    for (i, c) in expression.chars().enumerate() {
+        match Token::try_from(c).map_err(|e| e.at_column(i + 1))? {
+            Token::StartExpression => depth += 1,
+            Token::EndExpression if depth == 0 => {
+                return Err(Error::parse("unmatched ')'").at_column(i + 1))
+            }
+            Token::EndExpression => depth -= 1,
+            _ => (),
         }
     }
+    if depth > 0 {
+        return Err(Error::parse("unmatched '('"));
+    }
+    Ok(expression.to_owned())
 }
 
 impl Token {
@@ -58,12 +80,11 @@ impl Token {
     }
 }
 
+// TODO: node is never constructed?? what??
+#[allow(dead_code)]
 #[derive(Default, Debug)]
 struct Node {
     value: Token,
-
-    // TODO: field is never read?? what??
-    #[allow(dead_code)]
     children: Vec<Node>,
 }
 
@@ -97,7 +118,7 @@ fn reduce(expression: &str) -> i64 {
     let mut stack = vec![];
     let mut state = State::default();
     for t in expression.as_bytes() {
-        let token = Token::from(*t as char);
+        let token = Token::try_from(*t as char).expect("validated while parsing");
         if token.is_operator() {
             state.operator = token;
         } else if token == Token::StartExpression {
@@ -117,7 +138,7 @@ fn reduce(expression: &str) -> i64 {
 fn part_one(expressions: &[String]) -> i64 {
     let mut sum = 0;
     for e in expressions {
-        sum += reduce(&e);
+        sum += reduce(e);
     }
     sum
 }
@@ -126,7 +147,7 @@ fn infix_to_postfix(expression: &str) -> Vec<Token> {
     let mut stack = vec![];
     let mut postfix = vec![];
     for t in expression.as_bytes() {
-        let token = Token::from(*t as char);
+        let token = Token::try_from(*t as char).expect("validated while parsing");
         if token == Token::None {
             continue;
         }
@@ -176,27 +197,47 @@ fn evaluate_postfix(postfix: &[Token]) -> i64 {
 }
 
 fn reduce_with_priority(expression: &str) -> i64 {
-    evaluate_postfix(&infix_to_postfix(&expression))
+    evaluate_postfix(&infix_to_postfix(expression))
 }
 
 fn part_two(expressions: &[String]) -> i64 {
     let mut sum = 0;
     for e in expressions {
-        sum += reduce_with_priority(&e);
+        sum += reduce_with_priority(e);
     }
     sum
 }
 
-pub fn solve() -> String {
-    let expressions = Vec::<String>::load("input/day_eighteen.txt");
+pub struct DayEighteen;
+
+impl Solver for DayEighteen {
+    type Input = Vec<String>;
+
+    fn day(&self) -> u32 {
+        18
+    }
+
+    fn title(&self) -> &'static str {
+        "Operation Order"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some("input/day_eighteen.txt")
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        load_lines(filename, validate)
+    }
 
-    format!(
-        "part one: {}, part two: {}",
+    fn part_one(&self, expressions: &Self::Input) -> Result<Answer, Error> {
         // TODO: should be able to refactor part one in terms of part two,
         // just using the same priority.
-        part_one(&expressions),
-        part_two(&expressions)
-    )
+        Ok(part_one(expressions).into())
+    }
+
+    fn part_two(&self, expressions: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(expressions).into())
+    }
 }
 
 #[cfg(test)]
@@ -207,7 +248,7 @@ mod tests {
     fn test_solve() {
         assert_eq!(
             "part one: 50956598240016, part two: 535809575344339",
-            solve()
+            DayEighteen.solve()
         );
     }
 
@@ -238,4 +279,17 @@ mod tests {
             reduce_with_priority("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2")
         );
     }
+
+    #[test]
+    fn test_invalid_expressions() {
+        assert_eq!(
+            "column 5: unexpected '-' in expression",
+            validate("1 + - 2").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "column 6: unmatched ')'",
+            validate("1 + 2)").unwrap_err().to_string()
+        );
+        assert_eq!("unmatched '('", validate("(1 + 2").unwrap_err().to_string());
+    }
 }
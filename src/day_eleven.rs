@@ -1,5 +1,11 @@
+use crate::automaton::{
+    Automaton, DenseAutomaton, Experiment, LineOfSight, Neighborhood, State, Topology, ALIVE, DEAD,
+};
+use crate::error::Error;
 use crate::loadable::LoadableFromFile;
-use crate::yet_another_geometry_mod::*;
+use crate::solver::{Answer, Part, Solver};
+use crate::yet_another_geometry_mod::{Matrix2D, Point2D};
+use std::convert::TryFrom;
 
 /*
 If a seat is empty (L) and there are no occupied seats adjacent to it, the seat becomes occupied.
@@ -7,148 +13,129 @@ If a seat is occupied (#) and four or more seats adjacent to it are also occupie
 Otherwise, the seat's state does not change.
 */
 
-const EMPTY_SEAT: char = 'L';
-const FULL_SEAT: char = '#';
-const FLOOR: char = '.';
-
-const NEIGHBORING_UNIT_INDICES: [Point2D; 8] = [
-    Point2D { x: -1, y: -1 },
-    Point2D { x: -1, y: 0 },
-    Point2D { x: -1, y: 1 },
-    Point2D { x: 0, y: 1 },
-    Point2D { x: 1, y: 1 },
-    Point2D { x: 1, y: 0 },
-    Point2D { x: 1, y: -1 },
-    Point2D { x: 0, y: -1 },
-];
-
-fn count_full(matrix: &Matrix2D<char>) -> usize {
-    matrix.data.iter().filter(|&c| *c == FULL_SEAT).count()
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Seat {
+    Floor,
+    Empty,
+    Full,
 }
 
-fn in_bounds(point: &Point2D, matrix: &Matrix2D<char>) -> bool {
-    0 <= point.x && point.x < matrix.size.x && 0 <= point.y && point.y < matrix.size.y
-}
+impl TryFrom<char> for Seat {
+    type Error = Error;
 
-fn neighbors_met(matrix: &Matrix2D<char>, point: &Point2D, threshold: i64) -> bool {
-    let mut count = 0;
-    let mut current;
-    for index in NEIGHBORING_UNIT_INDICES.iter() {
-        current = *point + *index;
-        if in_bounds(&current, matrix) && matrix.get(&current) == FULL_SEAT {
-            count += 1;
-            if threshold > 0 && count == threshold {
-                return true;
-            }
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Seat::Floor),
+            'L' => Ok(Seat::Empty),
+            '#' => Ok(Seat::Full),
+            _ => Err(Error::parse(format!("unexpected '{}' in seat layout", c))),
         }
     }
-    // True IFF threshold is zero.
-    count == threshold
 }
 
-fn visible_met(matrix: &Matrix2D<char>, point: &Point2D, threshold: i64) -> bool {
-    let mut count = 0;
-    let mut current;
-    for index in NEIGHBORING_UNIT_INDICES.iter() {
-        current = *point + *index;
-        // We only care about the first visible seat (meaning first non-floor).
-        while in_bounds(&current, matrix) && matrix.get(&current) == FLOOR {
-            current += *index;
-        }
-        if in_bounds(&current, matrix) && matrix.get(&current) == FULL_SEAT {
-            count += 1;
-            if threshold > 0 && count == threshold {
-                return true;
-            }
-        }
-    }
-    // True IFF threshold is zero.
-    count == threshold
+// Empty seats fill up when nobody is around them, full ones empty out once
+// four others are, or five when looking further.
+const ADJACENT_RULE: &str = "B0/S0123";
+const VISIBLE_RULE: &str = "B0/S01234";
+// The seats' own rules always settle down, others might not.
+const MAX_STEPS: usize = 10_000;
+// Only looked for once the seats failed to settle, to say why.
+const MAX_PERIOD: usize = 100;
+
+// The floor never changes and nobody sits there, so it isn't a cell.
+fn cells(matrix: &Matrix2D<Seat>) -> Matrix2D<Option<State>> {
+    Matrix2D::from_fn(matrix.size, |p| match matrix[p] {
+        Seat::Floor => None,
+        Seat::Empty => Some(DEAD),
+        Seat::Full => Some(ALIVE),
+    })
 }
 
-fn prep(matrix: &mut Matrix2D<char>) {
-    for c in matrix.data.iter_mut() {
-        match *c {
-            FULL_SEAT => panic!("don't prep already running matrices"),
-            EMPTY_SEAT => *c = FULL_SEAT,
-            _ => (),
+// Runs until the seats stop changing, or for as many steps as |experiment|
+// says.
+fn simulate<T: Topology<Point2D>>(
+    matrix: &Matrix2D<Seat>,
+    topology: T,
+    rule: &str,
+    experiment: &Experiment,
+) -> Result<i64, Error> {
+    let mut seats = DenseAutomaton::new(cells(matrix), topology, experiment.rule_or(rule));
+    match experiment.steps {
+        Some(steps) => seats.step_n(steps),
+        None => {
+            if seats.run_until_stable(MAX_STEPS).is_none() {
+                return Err(Error::parse(match seats.find_cycle(MAX_PERIOD) {
+                    Some(cycle) => format!(
+                        "seats never settle, they repeat every {} steps",
+                        cycle.length
+                    ),
+                    None => format!("seats are still changing after {} steps", MAX_STEPS),
+                }));
+            }
         }
     }
+    Ok(seats.population() as i64)
 }
 
-fn swap_if_should(
-    matrix: &Matrix2D<char>,
-    next: &mut Matrix2D<char>,
-    index: &Point2D,
-    only_immediate: bool,
-) -> bool {
-    let seat = matrix.get(index);
-    if seat != EMPTY_SEAT && seat != FULL_SEAT {
-        return false;
+fn run(matrix: &Matrix2D<Seat>, part: Part, experiment: &Experiment) -> Result<i64, Error> {
+    match part {
+        Part::One => simulate(matrix, Neighborhood::square8(), ADJACENT_RULE, experiment),
+        // Only the first seat in each direction counts, however much floor is
+        // in between.
+        Part::Two => simulate(
+            matrix,
+            LineOfSight::new(matrix, |seat| *seat != Seat::Floor),
+            VISIBLE_RULE,
+            experiment,
+        ),
     }
+}
 
-    let empty = seat == EMPTY_SEAT;
-    let threshold = if empty {
-        0
-    } else {
-        if only_immediate {
-            4
-        } else {
-            5
-        }
-    };
-    let met = if only_immediate {
-        neighbors_met(matrix, index, threshold)
-    } else {
-        visible_met(matrix, index, threshold)
-    };
-    if met {
-        next.set(index, if empty { FULL_SEAT } else { EMPTY_SEAT });
-    }
+fn part_one(matrix: &Matrix2D<Seat>) -> Result<i64, Error> {
+    run(matrix, Part::One, &Experiment::default())
+}
 
-    met
+fn part_two(matrix: &Matrix2D<Seat>) -> Result<i64, Error> {
+    run(matrix, Part::Two, &Experiment::default())
 }
 
-fn run_iter(matrix: &Matrix2D<char>, next: &mut Matrix2D<char>, only_immediate: bool) -> bool {
-    let mut xy;
-    let mut something_changed = false;
-    for x in 0..matrix.size.x {
-        for y in 0..matrix.size.y {
-            xy = Point2D { x: x, y: y };
-            something_changed |= swap_if_should(matrix, next, &xy, only_immediate);
-        }
+pub struct DayEleven;
+
+impl Solver for DayEleven {
+    type Input = Matrix2D<Seat>;
+
+    fn day(&self) -> u32 {
+        11
     }
 
-    something_changed
-}
+    fn title(&self) -> &'static str {
+        "Seating System"
+    }
 
-fn run_until_halted(matrix: &mut Matrix2D<char>, only_immediate: bool) {
-    prep(matrix);
-    let mut next = matrix.clone();
-    while run_iter(matrix, &mut next, only_immediate) {
-        *matrix = next.clone();
+    fn default_input(&self) -> Option<&'static str> {
+        Some("input/day_eleven.txt")
     }
-}
 
-fn part_one(matrix: &Matrix2D<char>) -> i64 {
-    let mut m = matrix.clone();
-    run_until_halted(&mut m, true);
-    count_full(&m) as i64
-}
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Matrix2D::<Seat>::try_load(filename)
+    }
 
-fn part_two(matrix: &Matrix2D<char>) -> i64 {
-    let mut m = matrix.clone();
-    run_until_halted(&mut m, false);
-    count_full(&m) as i64
-}
+    fn part_one(&self, matrix: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(matrix)?.into())
+    }
+
+    fn part_two(&self, matrix: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(matrix)?.into())
+    }
 
-pub fn solve() -> String {
-    let matrix = Matrix2D::<char>::load("input/day_eleven.txt");
-    format!(
-        "part one: {}, part two: {}",
-        part_one(&matrix),
-        part_two(&matrix)
-    )
+    fn run_automaton(
+        &self,
+        matrix: &Self::Input,
+        part: Part,
+        experiment: &Experiment,
+    ) -> Option<Result<Answer, Error>> {
+        Some(run(matrix, part, experiment).map(Answer::from))
+    }
 }
 
 #[cfg(test)]
@@ -157,18 +144,76 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 2438, part two: 2174", solve());
+        assert_eq!("part one: 2438, part two: 2174", DayEleven.solve());
     }
 
     #[test]
     fn test_example() {
-        let matrix = Matrix2D::<char>::load("input/day_eleven_example.txt");
-        assert_eq!(37, part_one(&matrix));
+        let matrix = Matrix2D::<Seat>::load("input/day_eleven_example.txt");
+        assert_eq!(37, part_one(&matrix).unwrap());
     }
 
     #[test]
     fn test_example_part_two() {
-        let matrix = Matrix2D::<char>::load("input/day_eleven_example.txt");
-        assert_eq!(26, part_two(&matrix));
+        let matrix = Matrix2D::<Seat>::load("input/day_eleven_example.txt");
+        assert_eq!(26, part_two(&matrix).unwrap());
+    }
+
+    #[test]
+    fn test_experiment() {
+        let matrix = Matrix2D::<Seat>::load("input/day_eleven_example.txt");
+        let first_round = Experiment {
+            rule: None,
+            steps: Some(1),
+        };
+        // Everyone sits down at once.
+        assert_eq!(
+            Answer::Number(71),
+            DayEleven
+                .run_automaton(&matrix, Part::One, &first_round)
+                .unwrap()
+                .unwrap()
+        );
+        // Seats that are only ever taken, never left, fill up.
+        let never_leave = Experiment {
+            rule: Some("B0/S012345678".parse().unwrap()),
+            steps: None,
+        };
+        assert_eq!(
+            Answer::Number(71),
+            DayEleven
+                .run_automaton(&matrix, Part::Two, &never_leave)
+                .unwrap()
+                .unwrap()
+        );
+        // Everyone stands up and sits down again, forever.
+        let restless = Experiment {
+            rule: Some("B012345678/S".parse().unwrap()),
+            steps: None,
+        };
+        assert_eq!(
+            "seats never settle, they repeat every 2 steps",
+            DayEleven
+                .run_automaton(&matrix, Part::One, &restless)
+                .unwrap()
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_malformed_layout() {
+        assert_eq!(
+            "1:2: unexpected '@' in seat layout",
+            Matrix2D::<Seat>::try_load_str("L@\n..")
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "2: expected 2 columns like the longest row, found 1",
+            Matrix2D::<Seat>::try_load_str("L#\n.")
+                .unwrap_err()
+                .to_string()
+        );
     }
 }
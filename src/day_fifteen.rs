@@ -1,4 +1,6 @@
+use crate::error::Error;
 use crate::loadable::LoadableFromFile;
+use crate::solver::{Answer, Solver};
 
 // The maximum spoken number was found experimentally by running
 // part two and checking the largest spoken number.  In production
@@ -7,9 +9,9 @@ const MAXIMUM_SPOKEN_NUMBER: usize = 29473358;
 
 // NOTE: use of i32 here, while annoying, causes 1/2 of memory to be used
 // and a subsequence ~250ms speedup in release.
-fn part_one(starting_numbers: &[i64], term: usize, cache: &mut Vec<i32>) -> i64 {
-    for i in 0..starting_numbers.len() {
-        cache[starting_numbers[i] as usize] = i as i32;
+fn part_one(starting_numbers: &[i64], term: usize, cache: &mut [i32]) -> i64 {
+    for (i, &n) in starting_numbers.iter().enumerate() {
+        cache[n as usize] = i as i32;
     }
     let mut last_spoken = 0;
     // The prompt is 1-indexed, but we are 0-indexed.
@@ -30,18 +32,38 @@ fn create_cache() -> Vec<i32> {
     vec![-1; MAXIMUM_SPOKEN_NUMBER + 1]
 }
 
-pub fn solve() -> String {
-    let mut cache = create_cache();
-    let numbers = Vec::<i64>::load("input/day_fifteen.txt");
-    format!(
-        "part one: {}, part two: {}",
-        part_one(&numbers, 2020, &mut cache.clone()),
+pub struct DayFifteen;
+
+impl Solver for DayFifteen {
+    type Input = Vec<i64>;
+
+    fn day(&self) -> u32 {
+        15
+    }
+
+    fn title(&self) -> &'static str {
+        "Rambunctious Recitation"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some("input/day_fifteen.txt")
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Vec::<i64>::try_load(filename)
+    }
+
+    fn part_one(&self, numbers: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(numbers, 2020, &mut create_cache()).into())
+    }
+
+    fn part_two(&self, numbers: &Self::Input) -> Result<Answer, Error> {
         // Turns out the sequence has no cycles or patterns in variances after manual
         // inspection. Some digging shows that this sequence is actually Van Eck's,
         // which does not have a closed form. Performance comes from optimizations,
         // not reducing algorithmic complexity.
-        part_one(&numbers, 30000000, &mut cache)
-    )
+        Ok(part_one(numbers, 30000000, &mut create_cache()).into())
+    }
 }
 
 #[cfg(test)]
@@ -50,7 +72,7 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 203, part two: 9007186", solve());
+        assert_eq!("part one: 203, part two: 9007186", DayFifteen.solve());
     }
 
     #[test]
@@ -1,10 +1,9 @@
-use crate::loadable::LoadableFromFile;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
+use crate::error::Error;
+use crate::loadable::{parse_lines, LoadableFromFile};
+use crate::solver::{Answer, Solver};
 use std::str::FromStr;
 
-const INPUT_FILENAME: &'static str = "input/day_five.txt";
+const INPUT_FILENAME: &str = "input/day_five.txt";
 
 #[derive(Debug, Default, PartialEq, Eq, Copy, Clone)]
 pub struct BoardingPass {
@@ -44,30 +43,34 @@ impl BinarySearch for Range {
 }
 
 impl FromStr for BoardingPass {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.len() != 10 {
+            return Err(Error::parse(format!(
+                "boarding passes are 10 characters, found {}",
+                s.len()
+            )));
+        }
+
         let mut row_range = Range {
             left: 0,
             right: 127,
         };
-        for i in 0..7 {
-            if s.as_bytes()[i] as char == 'F' {
-                row_range.pick_left();
-            } else {
-                row_range.pick_right();
-            }
-        }
-        assert_eq!(row_range.left, row_range.right);
-
         let mut col_range = Range { left: 0, right: 7 };
-        for i in 7..10 {
-            if s.as_bytes()[i] as char == 'L' {
-                col_range.pick_left()
-            } else {
-                col_range.pick_right()
+        for (i, c) in s.chars().enumerate() {
+            match (i, c) {
+                (0..=6, 'F') => row_range.pick_left(),
+                (0..=6, 'B') => row_range.pick_right(),
+                (7..=9, 'L') => col_range.pick_left(),
+                (7..=9, 'R') => col_range.pick_right(),
+                _ => {
+                    return Err(Error::parse(format!("unexpected '{}' in boarding pass", c))
+                        .at_column(i + 1))
+                }
             }
         }
+        assert_eq!(row_range.left, row_range.right);
         assert_eq!(col_range.left, col_range.right);
 
         Ok(BoardingPass {
@@ -79,14 +82,8 @@ impl FromStr for BoardingPass {
 }
 
 impl LoadableFromFile for Vec<BoardingPass> {
-    fn load(filename: &str) -> Vec<BoardingPass> {
-        let file = File::open(filename).expect("Invalid filename");
-        let reader = BufReader::new(file);
-
-        reader
-            .lines()
-            .map(|l| BoardingPass::from_str(&l.unwrap()).unwrap())
-            .collect()
+    fn try_load_str(contents: &str) -> Result<Vec<BoardingPass>, Error> {
+        parse_lines(contents, BoardingPass::from_str)
     }
 }
 
@@ -101,21 +98,42 @@ fn part_one(passes: &[BoardingPass]) -> i64 {
 fn part_two(passes: &[BoardingPass]) -> i64 {
     let mut ids: Vec<i64> = passes.iter().map(|p| p.seat_id).collect();
     ids.sort();
-    for i in 1..ids.len() - 1 {
-        if ids[i] + 1 != ids[i + 1] {
-            return ids[i] + 1;
+    for pair in ids.windows(2) {
+        if pair[0] + 1 != pair[1] {
+            return pair[0] + 1;
         }
     }
-    return -1;
+    -1
 }
 
-pub fn solve() -> String {
-    let passes = Vec::<BoardingPass>::load(INPUT_FILENAME);
-    format!(
-        "part one: {}, part two: {}",
-        part_one(&passes),
-        part_two(&passes)
-    )
+pub struct DayFive;
+
+impl Solver for DayFive {
+    type Input = Vec<BoardingPass>;
+
+    fn day(&self) -> u32 {
+        5
+    }
+
+    fn title(&self) -> &'static str {
+        "Binary Boarding"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some(INPUT_FILENAME)
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Vec::<BoardingPass>::try_load(filename)
+    }
+
+    fn part_one(&self, passes: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(passes).into())
+    }
+
+    fn part_two(&self, passes: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(passes).into())
+    }
 }
 
 #[cfg(test)]
@@ -124,7 +142,7 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 892, part two: 625", solve());
+        assert_eq!("part one: 892, part two: 625", DayFive.solve());
     }
 
     #[test]
@@ -212,4 +230,15 @@ mod tests {
             BoardingPass::from_str("BBFFBBFRLL").unwrap()
         );
     }
+
+    #[test]
+    fn check_invalid_boarding_passes() {
+        assert_eq!(
+            "column 3: unexpected 'L' in boarding pass",
+            BoardingPass::from_str("BFLFBBFRRR")
+                .unwrap_err()
+                .to_string()
+        );
+        assert!(BoardingPass::from_str("BFFFBBFRR").is_err());
+    }
 }
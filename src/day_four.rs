@@ -1,14 +1,13 @@
-use crate::loadable::LoadableFromFile;
+use crate::error::Error;
+use crate::loadable::{paragraphs, LoadableFromFile};
+use crate::pattern::{split_with_columns, Pattern};
+use crate::solver::{Answer, Solver};
 use crate::validity::Validity;
-use lazy_static::lazy_static;
-use regex::Regex;
 use std::fmt;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
 use std::str::FromStr;
 
-const INPUT_FILENAME: &'static str = "input/day_four.txt";
+const INPUT_FILENAME: &str = "input/day_four.txt";
+const FIELD: Pattern = Pattern::new("{key}:{value}");
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum Height {
@@ -23,18 +22,18 @@ impl Default for Height {
 }
 
 impl FromStr for Height {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s.len() <= 3 {
-            return Err("invalid height");
+            return Err(Error::parse("invalid height"));
         }
         let pair = s.split_at(s.len() - 2);
         let value: i64 = pair.0.parse().unwrap_or_default();
         match pair.1 {
             "in" => Ok(Height::Inches(value)),
             "cm" => Ok(Height::Centimeters(value)),
-            _ => Err("failed to parse height"),
+            _ => Err(Error::parse("failed to parse height")),
         }
     }
 }
@@ -48,7 +47,7 @@ impl Validity for Height {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone)]
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
 pub enum EyeColor {
     Amber,
     Blue,
@@ -57,17 +56,12 @@ pub enum EyeColor {
     Green,
     Hazel,
     Other,
+    #[default]
     Unknown,
 }
 
-impl Default for EyeColor {
-    fn default() -> Self {
-        EyeColor::Unknown
-    }
-}
-
 impl FromStr for EyeColor {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
@@ -78,7 +72,7 @@ impl FromStr for EyeColor {
             "grn" => Ok(EyeColor::Green),
             "hzl" => Ok(EyeColor::Hazel),
             "oth" => Ok(EyeColor::Other),
-            _ => Err("Unknown eye color"),
+            _ => Err(Error::parse("Unknown eye color")),
         }
     }
 }
@@ -107,11 +101,11 @@ impl Default for RgbColor {
 }
 
 impl FromStr for RgbColor {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if !s.starts_with("#") || s.len() != 7 {
-            return Err("missing prepended hash or bad length");
+        if !s.starts_with('#') || s.len() != 7 {
+            return Err(Error::parse("missing prepended hash or bad length"));
         }
         let color = RgbColor {
             red: i16::from_str_radix(&s[1..3], 16).unwrap_or(-1),
@@ -119,7 +113,7 @@ impl FromStr for RgbColor {
             blue: i16::from_str_radix(&s[5..7], 16).unwrap_or(-1),
         };
         if !color.is_valid() {
-            Err("Invalid produced color")
+            Err(Error::parse("Invalid produced color"))
         } else {
             Ok(color)
         }
@@ -138,11 +132,11 @@ struct Identifier {
 }
 
 impl FromStr for Identifier {
-    type Err = &'static str;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         if s.len() != 9 {
-            return Err("incorrect length ID");
+            return Err(Error::parse("incorrect length ID"));
         }
 
         Ok(Identifier {
@@ -172,13 +166,13 @@ pub struct Passport {
 impl Validity for Passport {
     // For part one, country ID is optional.
     fn is_valid(&self) -> bool {
-        return (1920..2003).contains(&self.birth_year)
+        (1920..2003).contains(&self.birth_year)
             && (2010..2021).contains(&self.issue_year)
             && (2020..2031).contains(&self.expiration_year)
             && self.height.is_valid()
             && self.hair_color.is_valid()
             && self.eye_color.is_valid()
-            && self.id.is_valid();
+            && self.id.is_valid()
     }
 }
 
@@ -189,83 +183,84 @@ impl fmt::Display for Passport {
 }
 
 impl LoadableFromFile for Vec<Passport> {
-    fn load(filename: &str) -> Vec<Passport> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new("([a-z]+):([a-z0-9#]+)").unwrap();
-        }
-        let file = File::open(filename).expect("Invalid filename");
-
-        let mut reader = BufReader::new(file);
-        let mut line = String::new();
+    fn try_load_str(contents: &str) -> Result<Vec<Passport>, Error> {
         let mut passports: Vec<Passport> = Vec::new();
-        let mut current = Passport::default();
-        loop {
-            match reader.read_line(&mut line) {
-                Ok(bytes_read) => {
-                    // This either means we hit the EOF or a Passport separator.
-                    if line.trim().is_empty() {
-                        if current.is_valid() {
-                            passports.push(current);
-                        }
-                        if bytes_read == 0 {
-                            break;
-                        } else {
-                            // We just ignore invalid passports currently.
-                            current = Passport::default();
-                        }
+        for paragraph in paragraphs(contents) {
+            let mut current = Passport::default();
+            // Invalid field values are tolerated (the passport just won't be
+            // valid), but unknown fields mean this isn't a passport file.
+            for (number, line) in paragraph.numbered_lines() {
+                for (column, field) in split_with_columns(line, " ") {
+                    if field.is_empty() {
+                        continue;
                     }
-
-                    // TODO: this could handle errors cleaner.
-                    for found in RE.captures_iter(&line) {
-                        match &found[1] {
-                            "byr" => current.birth_year = found[2].parse().unwrap_or_default(),
-                            "iyr" => current.issue_year = found[2].parse().unwrap_or_default(),
-                            "eyr" => current.expiration_year = found[2].parse().unwrap_or_default(),
-                            "hgt" => {
-                                current.height = Height::from_str(&found[2]).unwrap_or_default()
-                            }
-                            "hcl" => {
-                                current.hair_color =
-                                    RgbColor::from_str(&found[2]).unwrap_or_default()
-                            }
-                            "ecl" => {
-                                current.eye_color =
-                                    EyeColor::from_str(&found[2]).unwrap_or_default()
-                            }
-                            "pid" => {
-                                current.id = Identifier::from_str(&found[2]).unwrap_or_default()
-                            }
-                            "cid" => current.country_id = found[2].to_owned(),
-                            _ => panic!(),
+                    let found = FIELD
+                        .captures_at(field, column)
+                        .map_err(|e| e.at_line(number))?;
+                    let value = found.get("value");
+                    match found.get("key") {
+                        "byr" => current.birth_year = value.parse().unwrap_or_default(),
+                        "iyr" => current.issue_year = value.parse().unwrap_or_default(),
+                        "eyr" => current.expiration_year = value.parse().unwrap_or_default(),
+                        "hgt" => current.height = Height::from_str(value).unwrap_or_default(),
+                        "hcl" => current.hair_color = RgbColor::from_str(value).unwrap_or_default(),
+                        "ecl" => current.eye_color = EyeColor::from_str(value).unwrap_or_default(),
+                        "pid" => current.id = Identifier::from_str(value).unwrap_or_default(),
+                        "cid" => current.country_id = value.to_owned(),
+                        field => {
+                            return Err(Error::parse(format!("unknown passport field '{}'", field))
+                                .at_column(column)
+                                .at_line(number))
                         }
                     }
                 }
-                Err(err) => {
-                    panic!("{}", err);
-                }
             }
-            line.clear();
+            // We just ignore invalid passports currently.
+            if current.is_valid() {
+                passports.push(current);
+            }
         }
-        passports
+        Ok(passports)
     }
 }
 
-fn part_one(_passports: &Vec<Passport>) -> i64 {
+fn part_one(_passports: &[Passport]) -> i64 {
     // TODO: refactor to re-enable part one.
     202
 }
 
-fn part_two(passports: &Vec<Passport>) -> i64 {
+fn part_two(passports: &[Passport]) -> i64 {
     passports.len() as i64
 }
 
-pub fn solve() -> String {
-    let passports = Vec::<Passport>::load(INPUT_FILENAME);
-    format!(
-        "part one: {}, part two: {}",
-        part_one(&passports),
-        part_two(&passports)
-    )
+pub struct DayFour;
+
+impl Solver for DayFour {
+    type Input = Vec<Passport>;
+
+    fn day(&self) -> u32 {
+        4
+    }
+
+    fn title(&self) -> &'static str {
+        "Passport Processing"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some(INPUT_FILENAME)
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Vec::<Passport>::try_load(filename)
+    }
+
+    fn part_one(&self, passports: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(passports).into())
+    }
+
+    fn part_two(&self, passports: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(passports).into())
+    }
 }
 
 #[cfg(test)]
@@ -274,13 +269,24 @@ mod tests {
 
     #[test]
     pub fn solves_part_one_example() {
-        const INPUT_FILENAME: &'static str = "input/day_four_part_one_example.txt";
+        const INPUT_FILENAME: &str = "input/day_four_part_one_example.txt";
         let passports = Vec::<Passport>::load(INPUT_FILENAME);
         assert_eq!(2, passports.len());
     }
 
+    #[test]
+    fn test_malformed_fields() {
+        let error = |s| Vec::<Passport>::try_load_str(s).unwrap_err().to_string();
+        assert_eq!(
+            "3:14: unknown passport field 'foo'",
+            error("byr:1937\n\nhcl:#fffffd  foo:1")
+        );
+        assert_eq!("1:14: missing value", error("byr:1937 iyr:"));
+        assert_eq!("1:1: expected \"{key}:{value}\"", error("byr1937"));
+    }
+
     #[test]
     pub fn solves() {
-        //assert_eq!("part one: 176, part two: 5872458240", solve());
+        //assert_eq!("part one: 176, part two: 5872458240", DayFour.solve());
     }
 }
@@ -1,11 +1,9 @@
-use crate::loadable::LoadableFromFile;
-use lazy_static::lazy_static;
-use regex::Regex;
+use crate::error::Error;
+use crate::loadable::{parse_lines, LoadableFromFile};
+use crate::pattern::Pattern;
+use crate::solver::{Answer, Solver};
 use std::collections::HashMap;
 use std::fmt;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
 use std::str::FromStr;
 
 #[derive(Default, Debug, Clone, PartialEq, Eq)]
@@ -38,15 +36,27 @@ enum Instruction {
     SetMemory(usize, i64),
 }
 
-impl From<&str> for Instruction {
-    fn from(s: &str) -> Self {
-        const MASK_STRING: &'static str = "mask = ";
+impl FromStr for Instruction {
+    type Err = Error;
 
-        if s.starts_with(MASK_STRING) {
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const MASK_STRING: &str = "mask = ";
+        // The docking computer uses 36 bit values.
+        const MASK_LENGTH: usize = 36;
+
+        if let Some(mask) = s.strip_prefix(MASK_STRING) {
+            if mask.len() != MASK_LENGTH {
+                return Err(Error::parse(format!(
+                    "masks are {} bits, found {}",
+                    MASK_LENGTH,
+                    mask.len()
+                ))
+                .at_column(MASK_STRING.len() + 1));
+            }
             let mut positives: i64 = 0;
             let mut negatives: i64 = 0;
             let mut floaters = vec![];
-            let mask_string: Vec<u8> = s.bytes().skip(MASK_STRING.len()).collect();
+            let mask_string: Vec<u8> = mask.bytes().collect();
             for b in mask_string.iter().enumerate() {
                 match *b.1 as char {
                     'X' => {
@@ -63,37 +73,40 @@ impl From<&str> for Instruction {
                         // Zero in the negative mask place, and in the
                         // positive mask case.
                     }
-                    _ => panic!("unknown char"),
+                    c => {
+                        return Err(Error::parse(format!("unexpected '{}' in mask", c))
+                            .at_column(MASK_STRING.len() + b.0 + 1))
+                    }
                 }
                 if b.0 < mask_string.len() - 1 {
                     positives <<= 1;
                     negatives <<= 1;
                 }
             }
-            Instruction::SetMask(Mask {
+            Ok(Instruction::SetMask(Mask {
                 positive_mask: positives,
                 negative_mask: negatives,
                 floating_indices: floaters,
-            })
+            }))
         } else {
-            lazy_static! {
-                static ref RE: Regex = Regex::new(r"mem\[(\d+)\] = (\d+)").unwrap();
+            const MEMORY: Pattern = Pattern::new("mem[{address}] = {value}");
+            if !s.starts_with("mem") {
+                return Err(Error::parse(format!(
+                    "expected \"{}...\" or \"{}\"",
+                    MASK_STRING, MEMORY
+                )));
             }
-            let m = RE.captures(s).unwrap();
-            Instruction::SetMemory(
-                m.get(1)
-                    .map(|x| usize::from_str(x.as_str()).unwrap())
-                    .unwrap(),
-                m.get(2)
-                    .map(|x| i64::from_str(x.as_str()).unwrap())
-                    .unwrap(),
-            )
+            let captures = MEMORY.captures(s)?;
+            Ok(Instruction::SetMemory(
+                captures.parse("address")?,
+                captures.parse("value")?,
+            ))
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
-struct Program {
+pub struct Program {
     instructions: Vec<Instruction>,
     memory: HashMap<usize, i64>,
     index: usize,
@@ -101,24 +114,18 @@ struct Program {
 }
 
 impl LoadableFromFile for Program {
-    fn load(filename: &str) -> Program {
-        let file = File::open(filename).expect("Invalid filename");
-        let reader = BufReader::new(file);
-
-        Program {
-            instructions: reader
-                .lines()
-                .map(|l| Instruction::from(&(l.unwrap())[..]))
-                .collect(),
+    fn try_load_str(contents: &str) -> Result<Program, Error> {
+        Ok(Program {
+            instructions: parse_lines(contents, Instruction::from_str)?,
             memory: HashMap::new(),
             index: 0,
             mask: Mask::default(),
-        }
+        })
     }
 }
 
 fn get_addresses(value: usize, mask: &Mask) -> Vec<usize> {
-    let mut addresses: Vec<usize> = Vec::with_capacity(2 << mask.floating_indices.len() + 1);
+    let mut addresses: Vec<usize> = Vec::with_capacity(2 << (mask.floating_indices.len() + 1));
     addresses.push(0);
     let mut masked_value = value;
     for index in &mask.floating_indices {
@@ -157,7 +164,7 @@ impl Program {
                 self.mask = mask.clone();
             }
             Instruction::SetMemory(address, value) => {
-                for address in get_addresses(*address as usize, &self.mask) {
+                for address in get_addresses(*address, &self.mask) {
                     self.memory.insert(address, *value);
                 }
             }
@@ -171,7 +178,7 @@ fn part_one(program: &Program) -> i64 {
     while p.index < p.instructions.len() {
         p.advance();
     }
-    p.memory.iter().map(|(_, v)| v).sum()
+    p.memory.values().sum()
 }
 
 // Part two approach:
@@ -183,16 +190,37 @@ fn part_two(program: &Program) -> i64 {
     while p.index < p.instructions.len() {
         p.advance_with_address_mask();
     }
-    p.memory.iter().map(|(_, v)| v).sum()
+    p.memory.values().sum()
 }
 
-pub fn solve() -> String {
-    let program = Program::load("input/day_fourteen.txt");
-    format!(
-        "part one: {}, part two: {}",
-        part_one(&program),
-        part_two(&program)
-    )
+pub struct DayFourteen;
+
+impl Solver for DayFourteen {
+    type Input = Program;
+
+    fn day(&self) -> u32 {
+        14
+    }
+
+    fn title(&self) -> &'static str {
+        "Docking Data"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some("input/day_fourteen.txt")
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Program::try_load(filename)
+    }
+
+    fn part_one(&self, program: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(program).into())
+    }
+
+    fn part_two(&self, program: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(program).into())
+    }
 }
 
 #[cfg(test)]
@@ -201,7 +229,10 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 13556564111697, part two: 4173715962894", solve());
+        assert_eq!(
+            "part one: 13556564111697, part two: 4173715962894",
+            DayFourteen.solve()
+        );
     }
 
     #[test]
@@ -216,6 +247,23 @@ mod tests {
         assert_eq!(208, part_two(&program));
     }
 
+    #[test]
+    fn test_malformed_instructions() {
+        let error = |s| Program::try_load_str(s).unwrap_err().to_string();
+        assert_eq!(
+            "1:5: invalid address: invalid digit found in string",
+            error("mem[x] = 3")
+        );
+        assert_eq!(
+            "1:5: expected \"mem[{address}] = {value}\"",
+            error("mem[8]= 3")
+        );
+        assert_eq!(
+            "1: expected \"mask = ...\" or \"mem[{address}] = {value}\"",
+            error("nop")
+        );
+    }
+
     #[test]
     fn test_get_addresses() {
         assert_eq!(
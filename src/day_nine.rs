@@ -1,9 +1,13 @@
+use crate::error::Error;
 use crate::loadable::LoadableFromFile;
+use crate::solver::{Answer, Solver};
 use std::collections::HashSet;
 
+const PREAMBLE_LENGTH: usize = 25;
+
 fn part_one(numbers: &[i64], preamble_length: usize) -> i64 {
     let mut set: HashSet<i64> = HashSet::new();
-    set.extend(numbers[0..preamble_length].into_iter());
+    set.extend(numbers[0..preamble_length].iter());
 
     for ei in numbers.iter().skip(preamble_length).enumerate() {
         let mut has_sum = false;
@@ -42,21 +46,41 @@ fn part_two(numbers: &[i64], weakness_number: i64) -> i64 {
 
     let mut min = numbers[left_index];
     let mut max = min;
-    for i in left_index + 1..right_index + 1 {
-        min = std::cmp::min(min, numbers[i]);
-        max = std::cmp::max(max, numbers[i]);
+    for &n in &numbers[left_index + 1..right_index + 1] {
+        min = std::cmp::min(min, n);
+        max = std::cmp::max(max, n);
     }
     min + max
 }
 
-pub fn solve() -> String {
-    let numbers = Vec::<i64>::load("input/day_nine.txt");
-    let weakness_number = part_one(&numbers, 25);
-    format!(
-        "part one: {}, part two: {}",
-        weakness_number,
-        part_two(&numbers, weakness_number)
-    )
+pub struct DayNine;
+
+impl Solver for DayNine {
+    type Input = Vec<i64>;
+
+    fn day(&self) -> u32 {
+        9
+    }
+
+    fn title(&self) -> &'static str {
+        "Encoding Error"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some("input/day_nine.txt")
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Vec::<i64>::try_load(filename)
+    }
+
+    fn part_one(&self, numbers: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(numbers, PREAMBLE_LENGTH).into())
+    }
+
+    fn part_two(&self, numbers: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(numbers, part_one(numbers, PREAMBLE_LENGTH)).into())
+    }
 }
 
 #[cfg(test)]
@@ -65,7 +89,7 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 530627549, part two: 77730285", solve());
+        assert_eq!("part one: 530627549, part two: 77730285", DayNine.solve());
     }
 
     #[test]
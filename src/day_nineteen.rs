@@ -1,12 +1,11 @@
-use crate::loadable::LoadableFromFile;
+use crate::error::Error;
+use crate::loadable::{paragraphs, LoadableFromFile};
+use crate::solver::{Answer, Solver};
 use regex::Regex;
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
 use std::str::FromStr;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 enum Rule {
     Single(char),
     Referential(Vec<Vec<usize>>),
@@ -18,60 +17,93 @@ struct RuleAndIndex {
     rule: Rule,
 }
 
-#[derive(Debug, Default)]
-struct Messages {
+#[derive(Debug, Default, Clone)]
+pub struct Messages {
     rules: HashMap<usize, Rule>,
     messages: Vec<String>,
 }
 
 impl FromStr for RuleAndIndex {
-    type Err = &'static str;
+    type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut index_and_rest = s.split(": ");
-        let index: usize = index_and_rest.next().unwrap().parse().unwrap();
-        let rest = index_and_rest.next().unwrap();
-
-        let rule;
-        if rest.starts_with("\"") {
-            if rest.len() != 3 {
-                return Err("invalid rule");
+        let (index, rest) = s
+            .split_once(": ")
+            .ok_or_else(|| Error::parse("expected a rule like \"0: 1 2\""))?;
+        let index: usize = index.parse().map_err(|e| Error::from(e).at_column(1))?;
+        // Column of the first character after the ": ".
+        let rest_column = s.len() - rest.len() + 1;
+
+        let rule = if rest.starts_with('"') {
+            if rest.len() != 3 || !rest.ends_with('"') {
+                return Err(
+                    Error::parse("expected a single quoted character").at_column(rest_column)
+                );
             }
-            rule = Rule::Single(rest.as_bytes()[1] as char);
+            Rule::Single(rest.as_bytes()[1] as char)
         } else {
-            rule = Rule::Referential(
-                rest.split(" | ")
-                    .map(|p| p.split_whitespace().collect())
-                    .map(|p: Vec<&str>| p.iter().map(|r| usize::from_str(r).unwrap()).collect())
-                    .collect(),
-            );
-        }
-        Ok(RuleAndIndex {
-            index: index,
-            rule: rule,
-        })
+            let mut alternatives = Vec::new();
+            let mut column = rest_column;
+            for alternative in rest.split(" | ") {
+                let mut sequence = Vec::new();
+                for r in alternative.split(' ') {
+                    sequence
+                        .push(usize::from_str(r).map_err(|e| Error::from(e).at_column(column))?);
+                    column += r.len() + 1;
+                }
+                alternatives.push(sequence);
+                column += "| ".len();
+            }
+            Rule::Referential(alternatives)
+        };
+        Ok(RuleAndIndex { index, rule })
     }
 }
 
 impl LoadableFromFile for Messages {
-    fn load(filename: &str) -> Self {
-        let file = File::open(filename).expect("Invalid filename");
-        let reader = BufReader::new(file);
+    fn try_load_str(contents: &str) -> Result<Self, Error> {
+        // The rules, then the messages, separated by a blank line.
+        let sections = paragraphs(contents);
+        let (rule_lines, messages) = match sections.as_slice() {
+            [rules] => (rules, vec![]),
+            [rules, messages] => (rules, messages.lines.clone()),
+            _ => {
+                return Err(Error::parse(format!(
+                    "expected rules and messages separated by a blank line, found {} sections",
+                    sections.len()
+                )))
+            }
+        };
 
-        let mut lines = reader.lines();
         let mut rules = HashMap::new();
-        loop {
-            match lines.next().unwrap().unwrap().trim() {
-                "" => break,
-                s => {
-                    let r = RuleAndIndex::from_str(s).unwrap();
-                    rules.insert(r.index, r.rule);
+        for (number, line) in rule_lines.numbered_lines() {
+            let r = RuleAndIndex::from_str(line.trim()).map_err(|e| e.at_line(number))?;
+            if rules.insert(r.index, r.rule).is_some() {
+                return Err(
+                    Error::parse(format!("rule {} is defined twice", r.index)).at_line(number)
+                );
+            }
+        }
+
+        // Catch dangling references now, rather than when building the regex.
+        for rule in rules.values() {
+            if let Rule::Referential(alternatives) = rule {
+                if let Some(missing) = alternatives
+                    .iter()
+                    .flatten()
+                    .find(|r| !rules.contains_key(r))
+                {
+                    return Err(Error::parse(format!("rule {} is never defined", missing)));
                 }
             }
         }
-        Messages {
-            rules: rules,
-            messages: lines.map(|l| l.unwrap()).collect(),
+        if !rules.contains_key(&0) {
+            return Err(Error::parse("rule 0 is never defined"));
         }
+
+        Ok(Messages {
+            rules,
+            messages: messages.into_iter().map(|l| l.to_owned()).collect(),
+        })
     }
 }
 
@@ -84,10 +116,9 @@ fn build_regex(
         return value.to_string();
     }
 
-    let fragment;
-    match rules.get(&r).unwrap() {
+    let fragment = match rules.get(&r).unwrap() {
         // Base case, string should be of length 1.
-        Rule::Single(c) => fragment = c.to_string(),
+        Rule::Single(c) => c.to_string(),
 
         Rule::Referential(rs) => {
             let mut regex = String::from("(");
@@ -103,9 +134,9 @@ fn build_regex(
                 regex.push(')');
             }
             regex.push(')');
-            fragment = regex;
+            regex
         }
-    }
+    };
 
     memo.insert(r, fragment);
     memo.get(&r).unwrap().to_string()
@@ -121,9 +152,8 @@ fn part_one(messages: &Messages) -> i64 {
     messages.messages.iter().filter(|m| re.is_match(m)).count() as i64
 }
 
-pub fn solve() -> String {
-    let mut messages = Messages::load("input/day_nineteen.txt");
-    let part_one_answer = part_one(&messages);
+fn part_two(messages: &Messages) -> i64 {
+    let mut messages = messages.clone();
 
     // Both 8 and 11 are special, self referencing rules that create cycles.
     // We can break the cycle at the beginning by using a recurse limit to
@@ -150,11 +180,37 @@ pub fn solve() -> String {
     }
     messages.rules.insert(11, Rule::Referential(eleven));
 
-    format!(
-        "part one: {}, part two: {}",
-        part_one_answer,
-        part_one(&messages)
-    )
+    part_one(&messages)
+}
+
+pub struct DayNineteen;
+
+impl Solver for DayNineteen {
+    type Input = Messages;
+
+    fn day(&self) -> u32 {
+        19
+    }
+
+    fn title(&self) -> &'static str {
+        "Monster Messages"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some("input/day_nineteen.txt")
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Messages::try_load(filename)
+    }
+
+    fn part_one(&self, messages: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(messages).into())
+    }
+
+    fn part_two(&self, messages: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(messages).into())
+    }
 }
 
 #[cfg(test)]
@@ -197,6 +253,20 @@ mod tests {
         assert_eq!("babbb", messages.messages[0]);
     }
 
+    #[test]
+    fn test_load_str() {
+        let messages = Messages::load_str("0: 1 1\n1: \"a\"\n\naa\nab\n");
+        assert_eq!(1, part_one(&messages));
+
+        let err = Messages::try_load_str("0: 1 2\n1: \"a\"\n\naa").unwrap_err();
+        assert_eq!("rule 2 is never defined", err.to_string());
+        let err = Messages::try_load_str("0: 1 x\n").unwrap_err();
+        assert_eq!(
+            "1:6: invalid number: invalid digit found in string",
+            err.to_string()
+        );
+    }
+
     #[test]
     fn test_example() {
         let messages = Messages::load("input/day_nineteen_example.txt");
@@ -205,6 +275,6 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 213, part two: 325", solve());
+        assert_eq!("part one: 213, part two: 325", DayNineteen.solve());
     }
 }
@@ -1,8 +1,8 @@
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
+use crate::error::Error;
+use crate::loadable::load_lines;
+use crate::solver::{Answer, Solver};
 
-const INPUT_FILENAME: &'static str = "input/day_one.txt";
+const INPUT_FILENAME: &str = "input/day_one.txt";
 const DESIRED_SUM: i32 = 2020;
 
 pub fn solve_part_one(data: &[i32], left: usize, right: usize, desired_sum: i32) -> Option<i32> {
@@ -26,15 +26,10 @@ pub fn solve_part_one(data: &[i32], left: usize, right: usize, desired_sum: i32)
 pub fn solve_part_two(data: &[i32], desired_sum: i32) -> Option<i32> {
     let mut part_two_answer: Option<i32> = None;
     for i in 0..(data.len() - 2) {
-        let partial = solve_part_one(
-            &data[i + 1..],
-            0,
-            data.len() - i - 2,
-            desired_sum - data[i] as i32,
-        );
+        let partial = solve_part_one(&data[i + 1..], 0, data.len() - i - 2, desired_sum - data[i]);
         match partial {
             Some(p) => {
-                part_two_answer = Some(p * data[i] as i32);
+                part_two_answer = Some(p * data[i]);
                 break;
             }
             None => continue,
@@ -43,29 +38,42 @@ pub fn solve_part_two(data: &[i32], desired_sum: i32) -> Option<i32> {
     part_two_answer
 }
 
-fn load_data() -> Vec<i32> {
-    let file = File::open(INPUT_FILENAME).expect("Invalid filename");
-    let reader = BufReader::new(file);
-
-    let mut data: Vec<i32> = reader
-        .lines()
-        .map(|r| match r {
-            Ok(n) => n.parse::<i32>().unwrap(),
-            Err(e) => panic!("{}", e),
-        })
-        .collect();
+fn load_data(filename: &str) -> Result<Vec<i32>, Error> {
+    let mut data = load_lines(filename, |l| Ok(l.parse::<i32>()?))?;
     data.sort();
-    data
+    Ok(data)
 }
 
-pub fn solve() -> String {
-    let data = load_data();
+pub struct DayOne;
+
+impl Solver for DayOne {
+    type Input = Vec<i32>;
+
+    fn day(&self) -> u32 {
+        1
+    }
 
-    format!(
-        "part one: {}, part two: {}",
-        solve_part_one(data.as_slice(), 0, data.len() - 1, DESIRED_SUM).unwrap(),
-        solve_part_two(data.as_slice(), DESIRED_SUM).unwrap()
-    )
+    fn title(&self) -> &'static str {
+        "Report Repair"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some(INPUT_FILENAME)
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        load_data(filename)
+    }
+
+    fn part_one(&self, data: &Self::Input) -> Result<Answer, Error> {
+        Ok(solve_part_one(data, 0, data.len() - 1, DESIRED_SUM)
+            .unwrap()
+            .into())
+    }
+
+    fn part_two(&self, data: &Self::Input) -> Result<Answer, Error> {
+        Ok(solve_part_two(data, DESIRED_SUM).unwrap().into())
+    }
 }
 
 #[cfg(test)]
@@ -74,6 +82,6 @@ mod tests {
 
     #[test]
     pub fn solves() {
-        assert_eq!("part one: 751776, part two: 42275090", solve());
+        assert_eq!("part one: 751776, part two: 42275090", DayOne.solve());
     }
 }
@@ -1,13 +1,14 @@
-use crate::loadable::LoadableFromFile;
-use lazy_static::lazy_static;
-use regex::Regex;
+use crate::error::Error;
+use crate::loadable::{parse_lines, LoadableFromFile};
+use crate::pattern::{split_with_columns, Pattern};
+use crate::solver::{Answer, Solver};
 use std::collections::HashMap;
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
 
-const SPECIAL_BAG_NAME: &'static str = "shiny gold";
+const SPECIAL_BAG_NAME: &str = "shiny gold";
+const RULE: Pattern = Pattern::new("{name} bags contain {children}.");
+// A trailing "s" is stripped first, so this matches "bags" too.
+const CHILD: Pattern = Pattern::new("{count} {name} bag");
 
 // Stage 1: lazy string lookups everywhere.
 #[derive(Debug, Clone)]
@@ -19,31 +20,36 @@ pub struct Bag {
 // TODO: not thrilled about all the Strings everywhere, but need to do more
 // research on lifetimes.
 impl LoadableFromFile for HashMap<String, Bag> {
-    fn load(filename: &str) -> HashMap<String, Bag> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"((\d+) ([a-z ]+)) bags?[., ]+").unwrap();
-        }
-        let file = File::open(filename).expect("invalid filename");
-        let reader = BufReader::new(file);
-
-        let mut bags = HashMap::<String, Bag>::new();
-        for line in reader.lines().map(|l| l.expect("bad input")) {
-            let name_or: Vec<&str> = line.splitn(2, " bags contain ").collect();
-
+    fn try_load_str(contents: &str) -> Result<HashMap<String, Bag>, Error> {
+        let bags = parse_lines(contents, |line| {
+            let rule = RULE.captures(line)?;
             let mut bag = Bag {
-                name: name_or[0].to_owned(),
+                name: rule.get("name").to_owned(),
                 children: HashMap::<String, i32>::new(),
             };
-            if !name_or[1].starts_with("n") {
-                for child in RE.captures_iter(&name_or[1]) {
-                    let count: i32 = child[2].parse().unwrap();
-                    bag.children.insert(child[3].to_string(), count);
+            let children = rule.get("children");
+            if children != "no other bags" {
+                for (column, child) in split_with_columns(children, ", ") {
+                    let child = child.strip_suffix('s').unwrap_or(child);
+                    let child = CHILD.captures_at(child, rule.column("children") + column - 1)?;
+                    bag.children
+                        .insert(child.get("name").to_owned(), child.parse("count")?);
                 }
             }
-            bags.insert(bag.name.to_string(), bag);
+            Ok(bag)
+        })?;
+
+        let bags: HashMap<String, Bag> = bags.into_iter().map(|b| (b.name.clone(), b)).collect();
+        // Counting recurses through children, so they all need to exist.
+        for bag in bags.values() {
+            if let Some(child) = bag.children.keys().find(|c| !bags.contains_key(*c)) {
+                return Err(Error::parse(format!(
+                    "{} bags contain unknown {} bags",
+                    bag.name, child
+                )));
+            }
         }
-
-        bags
+        Ok(bags)
     }
 }
 
@@ -65,10 +71,10 @@ fn find_bags_that_can_hold_gold(bags: &HashMap<String, Bag>) -> i64 {
             true
         });
 
-        if next_round.len() == 0 {
+        if next_round.is_empty() {
             break;
         }
-        can_carry.extend(next_round.into_iter());
+        can_carry.extend(next_round);
     }
 
     // We don't actually count the gold bag here.
@@ -84,7 +90,7 @@ fn find_total_bag_count(
         return memo[name];
     }
     let bag = &bags[name];
-    if bag.children.len() == 0 {
+    if bag.children.is_empty() {
         memo.insert(bag.name.to_string(), 1);
         return 1;
     }
@@ -105,13 +111,34 @@ fn find_total_bag_count_in_gold(bags: &HashMap<String, Bag>) -> i32 {
     find_total_bag_count(SPECIAL_BAG_NAME, bags, &mut memo) - 1
 }
 
-pub fn solve() -> String {
-    let bags = HashMap::<String, Bag>::load("input/day_seven.txt");
-    format!(
-        "part one: {}, part two: {}",
-        find_bags_that_can_hold_gold(&bags),
-        find_total_bag_count_in_gold(&bags)
-    )
+pub struct DaySeven;
+
+impl Solver for DaySeven {
+    type Input = HashMap<String, Bag>;
+
+    fn day(&self) -> u32 {
+        7
+    }
+
+    fn title(&self) -> &'static str {
+        "Handy Haversacks"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some("input/day_seven.txt")
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        HashMap::<String, Bag>::try_load(filename)
+    }
+
+    fn part_one(&self, bags: &Self::Input) -> Result<Answer, Error> {
+        Ok(find_bags_that_can_hold_gold(bags).into())
+    }
+
+    fn part_two(&self, bags: &Self::Input) -> Result<Answer, Error> {
+        Ok(find_total_bag_count_in_gold(bags).into())
+    }
 }
 
 #[cfg(test)]
@@ -120,7 +147,7 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 222, part two: 13264", solve());
+        assert_eq!("part one: 222, part two: 13264", DaySeven.solve());
     }
 
     #[test]
@@ -140,4 +167,25 @@ mod tests {
         let bags = HashMap::<String, Bag>::load("input/day_seven_example_three.txt");
         assert_eq!(126, find_total_bag_count_in_gold(&bags));
     }
+
+    #[test]
+    fn test_malformed_rules() {
+        let error = |s| {
+            HashMap::<String, Bag>::try_load_str(s)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            "1:35: invalid count: invalid digit found in string",
+            error("light red bags contain 1 red bag, two blue bags.")
+        );
+        assert_eq!(
+            "1:1: expected \"{name} bags contain {children}.\"",
+            error("light red bags hold 1 red bag.")
+        );
+        assert_eq!(
+            "light red bags contain unknown bright white bags",
+            error("light red bags contain 1 bright white bag.")
+        );
+    }
 }
@@ -1,143 +1,84 @@
+use crate::automaton::{Automaton, Experiment, Neighborhood, SparseAutomaton};
+use crate::error::Error;
 use crate::loadable::LoadableFromFile;
-use crate::yet_another_geometry_mod::{Matrix2D, Matrix2DLike, Point2D, Point3D, Point4D};
-use std::cmp::{Eq, PartialEq};
-use std::collections::HashMap;
-use std::collections::HashSet;
-use std::hash::Hash;
-use std::ops::Add;
-
-fn get_as_point(index: usize, x_width: i64) -> Point2D {
-    Point2D {
-        x: index as i64 % x_width,
-        y: index as i64 / x_width,
-    }
+use crate::solver::{Answer, Part, Solver};
+use crate::yet_another_geometry_mod::{Matrix2D, Point};
+
+// Conway's Game of Life, in more dimensions.
+const RULE: &str = "B3/S23";
+const CYCLES: usize = 6;
+
+// Lifeforms start out on a single slice, at zero in every other dimension.
+fn simulate<const N: usize>(
+    starting_lifeforms: &Matrix2D<char>,
+    experiment: &Experiment,
+) -> Result<i64, Error> {
+    let lifeforms = starting_lifeforms
+        .enumerate()
+        .filter(|(_, c)| **c == '#')
+        .map(|(p, _)| {
+            let mut point = Point::<N>::default();
+            point[0] = p.x;
+            point[1] = p.y;
+            point
+        });
+
+    let rule = experiment.rule_or(RULE);
+    let mut cubes = SparseAutomaton::new(lifeforms, Neighborhood::moore(), rule)?;
+    cubes.step_n(experiment.steps_or(CYCLES));
+    Ok(cubes.population() as i64)
 }
 
-const OFFSETS: [i64; 3] = [-1, 0, 1];
-
-fn get_neighbor_offsets_3d() -> Vec<Point3D> {
-    let mut offsets = Vec::with_capacity(OFFSETS.len().pow(3));
-    for xn in OFFSETS.iter() {
-        for yn in OFFSETS.iter() {
-            for zn in OFFSETS.iter() {
-                if *xn != 0 || *yn != 0 || *zn != 0 {
-                    offsets.push(Point3D {
-                        x: *xn,
-                        y: *yn,
-                        z: *zn,
-                    });
-                }
-            }
-        }
-    }
-    offsets
+fn part_one(starting_lifeforms: &Matrix2D<char>) -> Result<i64, Error> {
+    simulate::<3>(starting_lifeforms, &Experiment::default())
 }
 
-fn get_neighbor_offsets_4d() -> Vec<Point4D> {
-    let mut offsets = Vec::with_capacity(OFFSETS.len().pow(3));
-    for xn in OFFSETS.iter() {
-        for yn in OFFSETS.iter() {
-            for zn in OFFSETS.iter() {
-                for wn in OFFSETS.iter() {
-                    if *xn != 0 || *yn != 0 || *zn != 0 || *wn != 0 {
-                        offsets.push(Point4D {
-                            x: *xn,
-                            y: *yn,
-                            z: *zn,
-                            w: *wn,
-                        });
-                    }
-                }
-            }
-        }
-    }
-    offsets
+// Part two is the same as part one, except four dimensional.
+fn part_two(starting_lifeforms: &Matrix2D<char>) -> Result<i64, Error> {
+    simulate::<4>(starting_lifeforms, &Experiment::default())
 }
 
-fn run_iteration<P: PartialEq + Eq + Hash + Add + Copy>(
-    lifeforms: &HashSet<P>,
-    neighbor_offsets: &Vec<P>,
-) -> HashSet<P>
-where
-    P: Add<Output = P>,
-{
-    let mut possible_life = HashMap::<P, usize>::new();
-    for l in lifeforms.iter() {
-        for o in neighbor_offsets.iter() {
-            let point: P = *l + *o;
-
-            let entry;
-            if lifeforms.contains(&point) {
-                // Neighbor is already alive, so modify this life-form. The
-                // neighbor will be updated with this life form later.
-                entry = possible_life.entry(*l).or_insert(0);
-            } else {
-                // Neighbor is not alive yet, so doesn't impact this life-form,
-                // but may become alive itself.
-                entry = possible_life.entry(point).or_insert(0);
-            }
-            *entry += 1;
-        }
+pub struct DaySeventeen;
+
+impl Solver for DaySeventeen {
+    type Input = Matrix2D<char>;
+
+    fn day(&self) -> u32 {
+        17
     }
 
-    possible_life
-        .into_iter()
-        .filter(|kv| kv.1 == 3 || (kv.1 == 2 && lifeforms.contains(&kv.0)))
-        .map(|kv| kv.0)
-        .collect()
-}
+    fn title(&self) -> &'static str {
+        "Conway Cubes"
+    }
 
-fn part_one(starting_lifeforms: &Matrix2D<char>) -> i64 {
-    let mut lifeforms = HashSet::<Point3D>::new();
-
-    for s in starting_lifeforms.data.iter().enumerate() {
-        if *s.1 == '#' {
-            let p = get_as_point(s.0, starting_lifeforms.size().x);
-            lifeforms.insert(Point3D {
-                x: p.x,
-                y: p.y,
-                z: 0,
-            });
-        }
+    fn default_input(&self) -> Option<&'static str> {
+        Some("input/day_seventeen.txt")
     }
 
-    let offsets = get_neighbor_offsets_3d();
-    for _ in 0..6 {
-        lifeforms = run_iteration(&lifeforms, &offsets);
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Matrix2D::<char>::try_load(filename)
     }
-    lifeforms.len() as i64
-}
 
-// Part two is the same as part one, except four dimensional.
-fn part_two(starting_lifeforms: &Matrix2D<char>) -> i64 {
-    let mut lifeforms = HashSet::<Point4D>::new();
-
-    for s in starting_lifeforms.data.iter().enumerate() {
-        if *s.1 == '#' {
-            let p = get_as_point(s.0, starting_lifeforms.size().x);
-            lifeforms.insert(Point4D {
-                x: p.x,
-                y: p.y,
-                z: 0,
-                w: 0,
-            });
-        }
+    fn part_one(&self, starting_lifeforms: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(starting_lifeforms)?.into())
     }
 
-    let offsets = get_neighbor_offsets_4d();
-    for _ in 0..6 {
-        lifeforms = run_iteration(&lifeforms, &offsets);
+    fn part_two(&self, starting_lifeforms: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(starting_lifeforms)?.into())
     }
-    lifeforms.len() as i64
-}
 
-pub fn solve() -> String {
-    let starting_lifeforms = Matrix2D::<char>::load("input/day_seventeen.txt");
-    format!(
-        "part one: {}, part two: {}",
-        part_one(&starting_lifeforms),
-        part_two(&starting_lifeforms)
-    )
+    fn run_automaton(
+        &self,
+        starting_lifeforms: &Self::Input,
+        part: Part,
+        experiment: &Experiment,
+    ) -> Option<Result<Answer, Error>> {
+        let population = match part {
+            Part::One => simulate::<3>(starting_lifeforms, experiment),
+            Part::Two => simulate::<4>(starting_lifeforms, experiment),
+        };
+        Some(population.map(Answer::from))
+    }
 }
 
 #[cfg(test)]
@@ -146,18 +87,46 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 252, part two: 2160", solve());
+        assert_eq!("part one: 252, part two: 2160", DaySeventeen.solve());
     }
 
     #[test]
     fn test_example() {
         let starting_lifeforms = Matrix2D::<char>::load("input/day_seventeen_example.txt");
-        assert_eq!(112, part_one(&starting_lifeforms));
+        assert_eq!(112, part_one(&starting_lifeforms).unwrap());
     }
 
     #[test]
     fn test_example_part_two() {
         let starting_lifeforms = Matrix2D::<char>::load("input/day_seventeen_example.txt");
-        assert_eq!(848, part_two(&starting_lifeforms));
+        assert_eq!(848, part_two(&starting_lifeforms).unwrap());
+    }
+
+    #[test]
+    fn test_experiment() {
+        let starting_lifeforms = Matrix2D::<char>::load("input/day_seventeen_example.txt");
+        let after_one_cycle = Experiment {
+            rule: None,
+            steps: Some(1),
+        };
+        assert_eq!(
+            Answer::Number(11),
+            DaySeventeen
+                .run_automaton(&starting_lifeforms, Part::One, &after_one_cycle)
+                .unwrap()
+                .unwrap()
+        );
+        // Everything dies when nothing survives and nothing is born.
+        let extinction = Experiment {
+            rule: Some("B/S".parse().unwrap()),
+            steps: None,
+        };
+        assert_eq!(
+            Answer::Number(0),
+            DaySeventeen
+                .run_automaton(&starting_lifeforms, Part::Two, &extinction)
+                .unwrap()
+                .unwrap()
+        );
     }
 }
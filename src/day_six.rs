@@ -1,55 +1,39 @@
-use crate::loadable::LoadableFromFile;
+use crate::error::Error;
+use crate::loadable::{paragraphs, LoadableFromFile};
+use crate::solver::{Answer, Solver};
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
 
-const INPUT_FILENAME: &'static str = "input/day_six.txt";
+const INPUT_FILENAME: &str = "input/day_six.txt";
 
 type Group = Vec<HashSet<char>>;
 
 impl LoadableFromFile for Vec<Group> {
-    fn load(filename: &str) -> Vec<Group> {
-        let file = File::open(filename).expect("Invalid filename");
-        let mut reader = BufReader::new(file);
-        let mut line = String::new();
-        let mut groups: Vec<Group> = Vec::new();
-        let mut group = Group::default();
-        loop {
-            match reader.read_line(&mut line) {
-                Ok(bytes_read) => {
-                    // This either means we hit the EOF or a Group separator.
-                    line = line.trim().to_string();
-                    if line.is_empty() {
-                        groups.push(group);
-                        if bytes_read == 0 {
-                            break;
-                        } else {
-                            group = Group::default();
-                            continue;
+    fn try_load_str(contents: &str) -> Result<Vec<Group>, Error> {
+        // Every paragraph is a group, with one line per member.
+        paragraphs(contents)
+            .iter()
+            .map(|group| {
+                group.parse_lines(|line| {
+                    let mut answers = HashSet::<char>::default();
+                    for (j, c) in line.trim().chars().enumerate() {
+                        if !c.is_ascii_lowercase() {
+                            return Err(
+                                Error::parse(format!("'{}' is not a question", c)).at_column(j + 1)
+                            );
                         }
+                        answers.insert(c);
                     }
-
-                    let mut current = HashSet::<char>::default();
-                    for byte in line.as_bytes() {
-                        current.insert(*byte as char);
-                    }
-                    group.push(current);
-                }
-                Err(err) => {
-                    panic!("{}", err);
-                }
-            }
-            line.clear();
-        }
-        groups
+                    Ok(answers)
+                })
+            })
+            .collect()
     }
 }
 
 fn union(group: &Group) -> HashSet<char> {
     let mut out: HashSet<char> = group.first().unwrap().clone();
     for member in group.iter().skip(1) {
-        out = out.union(&member).copied().collect();
+        out = out.union(member).copied().collect();
     }
     out
 }
@@ -57,7 +41,7 @@ fn union(group: &Group) -> HashSet<char> {
 fn intersection(group: &Group) -> HashSet<char> {
     let mut out: HashSet<char> = group.first().unwrap().clone();
     for member in group.iter().skip(1) {
-        out = out.intersection(&member).copied().collect();
+        out = out.intersection(member).copied().collect();
     }
     out
 }
@@ -78,13 +62,34 @@ fn part_two(groups: &[Group]) -> usize {
     count
 }
 
-pub fn solve() -> String {
-    let groups = Vec::<Group>::load(INPUT_FILENAME);
-    format!(
-        "part one: {}, part two: {}",
-        part_one(&groups),
-        part_two(&groups)
-    )
+pub struct DaySix;
+
+impl Solver for DaySix {
+    type Input = Vec<Group>;
+
+    fn day(&self) -> u32 {
+        6
+    }
+
+    fn title(&self) -> &'static str {
+        "Custom Customs"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some(INPUT_FILENAME)
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Vec::<Group>::try_load(filename)
+    }
+
+    fn part_one(&self, groups: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(groups).into())
+    }
+
+    fn part_two(&self, groups: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(groups).into())
+    }
 }
 
 #[cfg(test)]
@@ -93,7 +98,7 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 6549, part two: 3466", solve());
+        assert_eq!("part one: 6549, part two: 3466", DaySix.solve());
     }
 
     #[test]
@@ -107,4 +112,17 @@ mod tests {
         let groups = Vec::<Group>::load("input/day_six_example.txt");
         assert_eq!(6, part_two(&groups));
     }
+
+    #[test]
+    fn test_crlf_and_trailing_blank_lines() {
+        let groups = Vec::<Group>::load_str("abc\r\n\r\na\r\nb\r\n\r\n\r\n");
+        assert_eq!(2, groups.len());
+        assert_eq!(5, part_one(&groups));
+        assert_eq!(
+            "4:2: 'B' is not a question",
+            Vec::<Group>::try_load_str("abc\n\na\nbB\n")
+                .unwrap_err()
+                .to_string()
+        );
+    }
 }
@@ -1,13 +1,16 @@
-use crate::loadable::LoadableFromFile;
-use lazy_static::lazy_static;
-use regex::Regex;
+use crate::error::Error;
+use crate::loadable::{paragraphs, LoadableFromFile};
+use crate::pattern::{split_with_columns, Pattern};
+use crate::solver::{Answer, Solver};
 use std::collections::HashSet;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
 use std::ops::Range;
 use std::str::FromStr;
 
+// Every ticket value, and every valid range, must be below this.
+const MAX_VALUE: usize = 1000;
+const FIELD: Pattern =
+    Pattern::new("{name}: {first_start}-{first_end} or {second_start}-{second_end}");
+
 #[derive(Debug)]
 struct TicketField {
     name: String,
@@ -24,25 +27,15 @@ fn contains(t: &TicketField, i: usize) -> bool {
 }
 
 impl FromStr for TicketField {
-    type Err = &'static str;
+    type Err = Error;
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(r"([a-z ]*): (\d*)-(\d*) or (\d*)-(\d*)").unwrap();
-        }
-        let cap = RE.captures(s);
-        if cap.is_none() {
-            return Err("not a ticket type");
-        }
-        let unwrapped = cap.unwrap();
-
-        fn as_usize(captures: &regex::Captures, index: usize) -> usize {
-            usize::from_str(captures.get(index).unwrap().as_str()).unwrap()
-        }
+        let captures = FIELD.captures(s)?;
+        let value = |name| captures.parse_with(name, value_from_str);
         Ok(TicketField {
-            name: unwrapped.get(1).unwrap().as_str().to_string(),
+            name: captures.get("name").to_owned(),
             valid_ranges: vec![
-                as_usize(&unwrapped, 2)..as_usize(&unwrapped, 3),
-                as_usize(&unwrapped, 4)..as_usize(&unwrapped, 5),
+                value("first_start")?..value("first_end")?,
+                value("second_start")?..value("second_end")?,
             ],
         })
     }
@@ -50,71 +43,76 @@ impl FromStr for TicketField {
 
 type Ticket = Vec<usize>;
 
-fn ticket_from_str(s: &str) -> Result<Ticket, &'static str> {
-    let trimmed = s.trim();
-    if trimmed.is_empty() {
-        Err("empty string")
-    } else {
-        Ok(trimmed
-            .split(",")
-            .map(|s| usize::from_str(s).unwrap())
-            .collect())
+fn value_from_str(s: &str) -> Result<usize, Error> {
+    let value = usize::from_str(s)?;
+    if value >= MAX_VALUE {
+        return Err(Error::parse(format!("values must be below {}", MAX_VALUE)));
     }
+    Ok(value)
 }
 
-struct Ticketing {
+fn ticket_from_str(s: &str) -> Result<Ticket, Error> {
+    split_with_columns(s.trim(), ",")
+        .map(|(column, value)| value_from_str(value).map_err(|e| e.at_column(column)))
+        .collect()
+}
+
+#[derive(Debug)]
+pub struct Ticketing {
     fields: Vec<TicketField>,
     your_ticket: Ticket,
     nearby_tickets: Vec<Ticket>,
 }
 
 impl LoadableFromFile for Ticketing {
-    fn load(filename: &str) -> Self {
-        let file = File::open(filename).expect("invalid filename");
-        let mut reader = BufReader::new(file);
-
-        let mut types = Vec::new();
-        let mut buf = String::new();
-        loop {
-            reader.read_line(&mut buf).expect("read");
-            let t = TicketField::from_str(&buf);
-            if t.is_err() {
-                break;
-            }
-            types.push(t.unwrap());
-            buf.clear();
+    fn try_load_str(contents: &str) -> Result<Self, Error> {
+        // The fields, your ticket and the nearby tickets are separated by
+        // blank lines.
+        let sections = paragraphs(contents);
+        if sections.len() != 3 {
+            return Err(Error::parse(format!(
+                "expected 3 sections separated by blank lines, found {}",
+                sections.len()
+            )));
         }
 
-        reader.read_line(&mut buf).expect("read");
-        assert_eq!("\nyour ticket:\n", buf);
-        buf.clear();
-        reader.read_line(&mut buf).expect("read");
-        let your_ticket = ticket_from_str(&buf).unwrap();
-        buf.clear();
-
-        reader.read_line(&mut buf).expect("read");
-        reader.read_line(&mut buf).expect("read");
-        assert_eq!("\nnearby tickets:\n", buf);
-        buf.clear();
-
-        let mut tickets = Vec::new();
-        loop {
-            if reader.read_line(&mut buf).is_err() {
-                break;
-            }
-            let t = ticket_from_str(&buf);
-            if t.is_err() {
-                break;
+        let fields = sections[0].parse_lines(TicketField::from_str)?;
+
+        // Every ticket needs a value for every field.
+        let check_length = |ticket: &Ticket, whose: &str, line: usize| {
+            if ticket.len() == fields.len() {
+                return Ok(());
             }
-            tickets.push(t.unwrap());
-            buf.clear();
+            Err(Error::parse(format!(
+                "expected {} values on {} ticket, found {}",
+                fields.len(),
+                whose,
+                ticket.len()
+            ))
+            .at_line(line))
+        };
+
+        let yours = sections[1].strip_header("your ticket:")?;
+        let mut your_tickets = yours.parse_lines(ticket_from_str)?;
+        if your_tickets.len() != 1 {
+            return Err(
+                Error::parse("expected exactly one ticket of your own").at_line(yours.first_line)
+            );
         }
+        let your_ticket = your_tickets.remove(0);
+        check_length(&your_ticket, "your", yours.first_line)?;
 
-        Ticketing {
-            fields: types,
-            your_ticket: your_ticket,
-            nearby_tickets: tickets,
+        let nearby = sections[2].strip_header("nearby tickets:")?;
+        let nearby_tickets = nearby.parse_lines(ticket_from_str)?;
+        for (ticket, (line, _)) in nearby_tickets.iter().zip(nearby.numbered_lines()) {
+            check_length(ticket, "a nearby", line)?;
         }
+
+        Ok(Ticketing {
+            fields,
+            your_ticket,
+            nearby_tickets,
+        })
     }
 }
 
@@ -127,9 +125,10 @@ struct SplitResult {
 }
 
 fn split_good_and_bad(ticketing: &Ticketing) -> SplitResult {
-    const MAX_INDEX: usize = 1000;
-    let mut result = SplitResult::default();
-    result.ticket_domain = vec![HashSet::<usize>::new(); MAX_INDEX];
+    let mut result = SplitResult {
+        ticket_domain: vec![HashSet::<usize>::new(); MAX_VALUE],
+        ..Default::default()
+    };
 
     // Building the ticket domain is pretty costly, at O(|F|*|R|*|R_L|).
     //      |F| = number of fields on each ticket
@@ -180,7 +179,7 @@ fn part_two(ticketing: &Ticketing, split: &SplitResult) -> i64 {
         for ticket in split.good_tickets.iter() {
             for candidate in candidates.clone().iter() {
                 let candidate_type = &ticketing.fields[*candidate];
-                if !contains(&candidate_type, ticket[i]) {
+                if !contains(candidate_type, ticket[i]) {
                     candidates.remove(candidate);
                 }
             }
@@ -202,14 +201,14 @@ fn part_two(ticketing: &Ticketing, split: &SplitResult) -> i64 {
     // Note that |F| is relatively small in comparison to |T| in our data set.
     loop {
         let mut changed_something = false;
-        for i in 0..candidate_fields.len() {
-            if candidate_fields[i].len() > 1 {
+        for candidates in candidate_fields.iter_mut() {
+            if candidates.len() > 1 {
                 changed_something = true;
-                for candidate in candidate_fields[i].clone() {
-                    if candidate_fields[i].len() > 1 && known_fields.contains(&candidate) {
-                        candidate_fields[i].remove(&candidate);
-                        if candidate_fields[i].len() == 1 {
-                            known_fields.insert(*candidate_fields[i].iter().next().unwrap());
+                for candidate in candidates.clone() {
+                    if candidates.len() > 1 && known_fields.contains(&candidate) {
+                        candidates.remove(&candidate);
+                        if candidates.len() == 1 {
+                            known_fields.insert(*candidates.iter().next().unwrap());
                             break;
                         }
                     }
@@ -229,26 +228,46 @@ fn part_two(ticketing: &Ticketing, split: &SplitResult) -> i64 {
     // Since |T| >(>?) than |F|, we really want to minimize |T| traversals where
     // possible.
     let mut result = 1;
-    for i in 0..ticketing.your_ticket.len() {
-        assert_eq!(1, candidate_fields[i].len());
-        if ticketing.fields[*candidate_fields[i].iter().next().unwrap()]
+    for (value, candidates) in ticketing.your_ticket.iter().zip(candidate_fields.iter()) {
+        assert_eq!(1, candidates.len());
+        if ticketing.fields[*candidates.iter().next().unwrap()]
             .name
             .starts_with("departure")
         {
-            result *= ticketing.your_ticket[i] as i64;
+            result *= *value as i64;
         }
     }
     result
 }
 
-pub fn solve() -> String {
-    let ticketing = Ticketing::load("input/day_sixteen.txt");
-    let split_tickets = split_good_and_bad(&ticketing);
-    format!(
-        "part one: {}, part two: {}",
-        split_tickets.error_rate,
-        part_two(&ticketing, &split_tickets)
-    )
+pub struct DaySixteen;
+
+impl Solver for DaySixteen {
+    type Input = Ticketing;
+
+    fn day(&self) -> u32 {
+        16
+    }
+
+    fn title(&self) -> &'static str {
+        "Ticket Translation"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some("input/day_sixteen.txt")
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Ticketing::try_load(filename)
+    }
+
+    fn part_one(&self, ticketing: &Self::Input) -> Result<Answer, Error> {
+        Ok(split_good_and_bad(ticketing).error_rate.into())
+    }
+
+    fn part_two(&self, ticketing: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(ticketing, &split_good_and_bad(ticketing)).into())
+    }
 }
 
 #[cfg(test)]
@@ -257,7 +276,10 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 21996, part two: 650080463519", solve());
+        assert_eq!(
+            "part one: 21996, part two: 650080463519",
+            DaySixteen.solve()
+        );
     }
 
     #[test]
@@ -266,4 +288,41 @@ mod tests {
         let split_tickets = split_good_and_bad(&tickets);
         assert_eq!(71, split_tickets.error_rate);
     }
+
+    #[test]
+    fn test_malformed_fields() {
+        let error = |s: &str| TicketField::from_str(s).unwrap_err().to_string();
+        assert_eq!(
+            "column 17: invalid number: invalid digit found in string",
+            error("row: 6-11 or 33-44 or 1-2")
+        );
+        assert_eq!(
+            "column 16: values must be below 1000",
+            error("seat: 1-3 or 5-1000")
+        );
+        assert_eq!(
+            "column 3: invalid number: invalid digit found in string",
+            ticket_from_str("1,x,3").unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    fn test_malformed_tickets() {
+        let error = |s: &str| Ticketing::try_load_str(s).unwrap_err().to_string();
+        let fields = "class: 1-3 or 5-7\nrow: 6-11 or 33-44\n\n";
+        assert_eq!(
+            "5: expected 2 values on your ticket, found 3",
+            error(&format!(
+                "{}your ticket:\n7,1,14\n\nnearby tickets:\n7,3",
+                fields
+            ))
+        );
+        assert_eq!(
+            "9: expected 2 values on a nearby ticket, found 1",
+            error(&format!(
+                "{}your ticket:\n7,1\n\nnearby tickets:\n7,3\n40",
+                fields
+            ))
+        );
+    }
 }
@@ -1,13 +1,50 @@
-fn part_one() -> i64 {
+use crate::error::Error;
+use crate::loadable::load_lines;
+use crate::solver::{Answer, Solver};
+
+const INPUT_FILENAME: &str = "input/day_template.txt";
+
+// TODO: parse each line into something more useful than a string.
+fn parse_line(line: &str) -> Result<String, Error> {
+    Ok(line.to_owned())
+}
+
+fn part_one(_lines: &[String]) -> i64 {
     0
 }
 
-fn part_two() -> i64 {
+fn part_two(_lines: &[String]) -> i64 {
     0
 }
 
-pub fn solve() -> String {
-    format!("part one: {}, part two: {}", part_one(), part_two())
+pub struct DayTemplate;
+
+impl Solver for DayTemplate {
+    type Input = Vec<String>;
+
+    fn day(&self) -> u32 {
+        0
+    }
+
+    fn title(&self) -> &'static str {
+        "Template"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some(INPUT_FILENAME)
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        load_lines(filename, parse_line)
+    }
+
+    fn part_one(&self, lines: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(lines).into())
+    }
+
+    fn part_two(&self, lines: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(lines).into())
+    }
 }
 
 #[cfg(test)]
@@ -16,6 +53,13 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 0, part two: 0", solve());
+        assert_eq!("part one: 0, part two: 0", DayTemplate.solve());
+    }
+
+    #[test]
+    fn test_example() {
+        let lines = DayTemplate.parse("input/day_template_example.txt").unwrap();
+        assert_eq!(0, part_one(&lines));
+        assert_eq!(0, part_two(&lines));
     }
 }
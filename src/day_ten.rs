@@ -1,4 +1,6 @@
+use crate::error::Error;
 use crate::loadable::LoadableFromFile;
+use crate::solver::{Answer, Solver};
 
 fn part_one(input: &[i64]) -> i64 {
     let mut one_diff = 0;
@@ -19,13 +21,13 @@ fn part_one(input: &[i64]) -> i64 {
     one_diff * three_diff
 }
 
-fn find_arrangements(input: &[i64], index: usize, memo: &mut Vec<Option<i64>>) -> i64 {
+fn find_arrangements(input: &[i64], index: usize, memo: &mut [Option<i64>]) -> i64 {
     if index == input.len() - 1 {
         return 1;
     }
 
-    if memo[index].is_some() {
-        return memo[index].unwrap();
+    if let Some(arrangements) = memo[index] {
+        return arrangements;
     }
 
     let mut total_arrangements = 0;
@@ -50,14 +52,36 @@ fn part_two(input: &[i64]) -> i64 {
     sum
 }
 
-pub fn solve() -> String {
-    let mut input = Vec::<i64>::load("input/day_ten.txt");
-    input.sort();
-    format!(
-        "part one: {}, part two: {}",
-        part_one(&input),
-        part_two(&input)
-    )
+pub struct DayTen;
+
+impl Solver for DayTen {
+    type Input = Vec<i64>;
+
+    fn day(&self) -> u32 {
+        10
+    }
+
+    fn title(&self) -> &'static str {
+        "Adapter Array"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some("input/day_ten.txt")
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        let mut input = Vec::<i64>::try_load(filename)?;
+        input.sort();
+        Ok(input)
+    }
+
+    fn part_one(&self, input: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(input).into())
+    }
+
+    fn part_two(&self, input: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(input).into())
+    }
 }
 
 #[cfg(test)]
@@ -66,7 +90,7 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 2048, part two: 1322306994176", solve());
+        assert_eq!("part one: 2048, part two: 1322306994176", DayTen.solve());
     }
 
     #[test]
@@ -1,28 +1,49 @@
+use crate::error::Error;
 use crate::loadable::LoadableFromFile;
-use std::fs;
+use crate::solver::{Answer, Solver};
 use std::str::FromStr;
 
-struct BusNotes {
+pub struct BusNotes {
     departure_time: i64,
     buses_in_service: Vec<(usize, i64)>,
 }
 
 impl LoadableFromFile for BusNotes {
-    fn load(filename: &str) -> Self {
-        let data = fs::read_to_string(filename).expect("Unable to read file");
-        let mut lines = data.lines();
-
-        BusNotes {
-            departure_time: lines.next().unwrap().parse().unwrap(),
-            buses_in_service: lines
-                .next()
-                .unwrap()
-                .split(",")
-                .enumerate()
-                .filter(|b| b.1 != "x")
-                .map(|b| (b.0, i64::from_str(b.1).unwrap()))
-                .collect(),
+    fn try_load_str(contents: &str) -> Result<Self, Error> {
+        let mut lines = contents.lines();
+        let mut next_line = |number: usize| {
+            lines.next().ok_or_else(|| {
+                Error::parse("expected a departure time and a list of buses").at_line(number)
+            })
+        };
+
+        let departure_time = next_line(1)?
+            .parse()
+            .map_err(|e| Error::from(e).at_line(1))?;
+
+        let mut buses_in_service = Vec::new();
+        let mut column = 1;
+        for b in next_line(2)?.split(',').enumerate() {
+            if b.1 != "x" {
+                let period =
+                    i64::from_str(b.1).map_err(|e| Error::from(e).at_column(column).at_line(2))?;
+                if period <= 0 {
+                    return Err(Error::parse("bus periods must be positive")
+                        .at_column(column)
+                        .at_line(2));
+                }
+                buses_in_service.push((b.0, period));
+            }
+            column += b.1.len() + 1;
         }
+        if buses_in_service.is_empty() {
+            return Err(Error::parse("no buses in service").at_line(2));
+        }
+
+        Ok(BusNotes {
+            departure_time,
+            buses_in_service,
+        })
     }
 }
 
@@ -76,13 +97,34 @@ fn part_two(buses: &[(usize, i64)]) -> i64 {
     rc.0
 }
 
-pub fn solve() -> String {
-    let notes = BusNotes::load("input/day_thirteen.txt");
-    format!(
-        "part one: {}, part two: {}",
-        part_one(&notes),
-        part_two(&notes.buses_in_service)
-    )
+pub struct DayThirteen;
+
+impl Solver for DayThirteen {
+    type Input = BusNotes;
+
+    fn day(&self) -> u32 {
+        13
+    }
+
+    fn title(&self) -> &'static str {
+        "Shuttle Search"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some("input/day_thirteen.txt")
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        BusNotes::try_load(filename)
+    }
+
+    fn part_one(&self, notes: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(notes).into())
+    }
+
+    fn part_two(&self, notes: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(&notes.buses_in_service).into())
+    }
 }
 
 #[cfg(test)]
@@ -91,7 +133,10 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 3966, part two: 800177252346225", solve());
+        assert_eq!(
+            "part one: 3966, part two: 800177252346225",
+            DayThirteen.solve()
+        );
     }
 
     #[test]
@@ -1,15 +1,35 @@
+use crate::error::Error;
 use crate::loadable::LoadableFromFile;
+use crate::solver::{Answer, Solver};
 use crate::yet_another_geometry_mod::*;
+use std::convert::TryFrom;
 
-const INPUT_FILENAME: &'static str = "input/day_three.txt";
+const INPUT_FILENAME: &str = "input/day_three.txt";
 
-fn part_one(map: &Matrix2D<char>, slope: &Point2D) -> i64 {
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Square {
+    Open,
+    Tree,
+}
+
+impl TryFrom<char> for Square {
+    type Error = Error;
+
+    fn try_from(c: char) -> Result<Self, Self::Error> {
+        match c {
+            '.' => Ok(Square::Open),
+            '#' => Ok(Square::Tree),
+            _ => Err(Error::parse(format!("unexpected '{}' in map", c))),
+        }
+    }
+}
+
+fn part_one(map: &Matrix2D<Square>, slope: &Point2D) -> i64 {
     let mut tree_count: i64 = 0;
     let mut current = Point2D { x: 0, y: 0 };
-    while current.y < map.size.y {
-        // The map is infinite in a repeating pattern, but only in the X direction.
-        current.x = current.x % map.size.x;
-        if map.get(&current) == '#' {
+    // The map is infinite in a repeating pattern, but only in the X direction.
+    while let Some(square) = map.get_wrapping(&current, Wrap::HORIZONTAL) {
+        if *square == Square::Tree {
             tree_count += 1;
         }
         current += *slope;
@@ -17,8 +37,8 @@ fn part_one(map: &Matrix2D<char>, slope: &Point2D) -> i64 {
     tree_count
 }
 
-fn part_two(map: &Matrix2D<char>) -> i64 {
-    static SLOPES: &'static [Point2D] = &[
+fn part_two(map: &Matrix2D<Square>) -> i64 {
+    static SLOPES: &[Point2D] = &[
         Point2D { x: 1, y: 1 },
         Point2D { x: 3, y: 1 },
         Point2D { x: 5, y: 1 },
@@ -33,13 +53,34 @@ fn part_two(map: &Matrix2D<char>) -> i64 {
     total
 }
 
-pub fn solve() -> String {
-    let map = Matrix2D::<char>::load(INPUT_FILENAME);
-    format!(
-        "part one: {}, part two: {}",
-        part_one(&map, &Point2D { x: 3, y: 1 }),
-        part_two(&map)
-    )
+pub struct DayThree;
+
+impl Solver for DayThree {
+    type Input = Matrix2D<Square>;
+
+    fn day(&self) -> u32 {
+        3
+    }
+
+    fn title(&self) -> &'static str {
+        "Toboggan Trajectory"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some(INPUT_FILENAME)
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Matrix2D::<Square>::try_load(filename)
+    }
+
+    fn part_one(&self, map: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(map, &Point2D { x: 3, y: 1 }).into())
+    }
+
+    fn part_two(&self, map: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(map).into())
+    }
 }
 
 #[cfg(test)]
@@ -49,12 +90,22 @@ mod tests {
     #[test]
     pub fn solves_part_one_example() {
         const SLOPE: Point2D = Point2D { x: 3, y: 1 };
-        let map = Matrix2D::<char>::load("input/day_three_part_one_example.txt");
+        let map = Matrix2D::<Square>::load("input/day_three_part_one_example.txt");
         assert_eq!(7, part_one(&map, &SLOPE));
     }
 
+    #[test]
+    fn test_malformed_map() {
+        assert_eq!(
+            "2:3: unexpected 'o' in map",
+            Matrix2D::<Square>::try_load_str("..#\n#.o\n")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
     #[test]
     pub fn solves() {
-        assert_eq!("part one: 176, part two: 5872458240", solve());
+        assert_eq!("part one: 176, part two: 5872458240", DayThree.solve());
     }
 }
@@ -1,8 +1,9 @@
-use crate::loadable::LoadableFromFile;
+use crate::error::Error;
+use crate::loadable::{parse_lines, LoadableFromFile};
+use crate::solver::{Answer, Solver};
 use crate::yet_another_geometry_mod::*;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
+use std::fmt;
+use std::str::FromStr;
 
 #[derive(Debug)]
 pub enum MoveAction {
@@ -15,113 +16,194 @@ pub enum MoveAction {
     Forward(i64),
 }
 
-impl From<&str> for MoveAction {
-    fn from(s: &str) -> Self {
+impl FromStr for MoveAction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() || !s.is_char_boundary(1) {
+            return Err(Error::parse("expected an action like \"F10\""));
+        }
         let action_and_value = s.split_at(1);
-        let value: i64 = action_and_value.1.parse().unwrap();
+        let value: i64 = action_and_value
+            .1
+            .parse()
+            .map_err(|e| Error::from(e).at_column(2))?;
+        // Only whole quarter turns make sense.
+        let turn_by = |value| {
+            quarter_turns(value)
+                .map(|_| value)
+                .map_err(|e| Error::parse(e.to_string()).at_column(2))
+        };
         match action_and_value.0 {
-            "N" => MoveAction::North(value),
-            "S" => MoveAction::South(value),
-            "E" => MoveAction::East(value),
-            "W" => MoveAction::West(value),
-            "L" => MoveAction::Left(value),
-            "R" => MoveAction::Right(value),
-            "F" => MoveAction::Forward(value),
-            _ => panic!("unknown action"),
+            "N" => Ok(MoveAction::North(value)),
+            "S" => Ok(MoveAction::South(value)),
+            "E" => Ok(MoveAction::East(value)),
+            "W" => Ok(MoveAction::West(value)),
+            "L" => Ok(MoveAction::Left(turn_by(value)?)),
+            "R" => Ok(MoveAction::Right(turn_by(value)?)),
+            "F" => Ok(MoveAction::Forward(value)),
+            a => Err(Error::parse(format!("unknown action '{}'", a)).at_column(1)),
         }
     }
 }
 
 impl LoadableFromFile for Vec<MoveAction> {
-    fn load(filename: &str) -> Self {
-        let file = File::open(filename).expect("invalid filename");
-        let reader = BufReader::new(file);
-        reader
-            .lines()
-            .map(|l| MoveAction::from(&l.unwrap()[..]))
-            .collect()
+    fn try_load_str(contents: &str) -> Result<Self, Error> {
+        parse_lines(contents, MoveAction::from_str)
+    }
+}
+
+const CHECKED_WHEN_PARSED: &str = "turns are checked when parsing";
+// The puzzle talks about north and east, so y grows northwards.
+const SYSTEM: CoordinateSystem = CoordinateSystem::Compass;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Ship {
+    position: Point2D,
+    // Directions turn the same in every system, only |SYSTEM| maps them to
+    // points.
+    heading: Direction,
+    // Relative to the ship, only used when navigating by waypoint.
+    waypoint: Point2D,
+}
+
+impl Default for Ship {
+    fn default() -> Self {
+        Ship {
+            position: Point2D::default(),
+            heading: Direction::EAST,
+            waypoint: Point2D { x: 10, y: 1 },
+        }
+    }
+}
+
+impl fmt::Display for Ship {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "at {} facing {}",
+            self.position,
+            self.heading.compass_name()
+        )
     }
 }
 
-fn apply_action(action: &MoveAction, point: &mut Point2D, direction: &mut Direction) {
-    match action {
-        MoveAction::North(value) => point.y += *value,
-        MoveAction::South(value) => point.y -= *value,
-        MoveAction::East(value) => point.x -= *value,
-        MoveAction::West(value) => point.x += *value,
-        MoveAction::Left(value) => {
-            *direction = match value {
-                90 => direction.to_left(),
-                180 => direction.inverse(),
-                270 => direction.to_right(),
-                _ => panic!("moving left: need to refactor this to support more degrees"),
+impl Ship {
+    fn apply(&mut self, action: &MoveAction) {
+        let position = &mut self.position;
+        match action {
+            MoveAction::North(value) => {
+                *position = SYSTEM.advance(*position, Direction::NORTH, *value)
+            }
+            MoveAction::South(value) => {
+                *position = SYSTEM.advance(*position, Direction::SOUTH, *value)
+            }
+            MoveAction::East(value) => {
+                *position = SYSTEM.advance(*position, Direction::EAST, *value)
+            }
+            MoveAction::West(value) => {
+                *position = SYSTEM.advance(*position, Direction::WEST, *value)
+            }
+            MoveAction::Left(value) => self.heading.rotate_left(*value).expect(CHECKED_WHEN_PARSED),
+            MoveAction::Right(value) => self
+                .heading
+                .rotate_right(*value)
+                .expect(CHECKED_WHEN_PARSED),
+            MoveAction::Forward(value) => {
+                *position = SYSTEM.advance(*position, self.heading, *value)
             }
         }
-        MoveAction::Right(value) => {
-            *direction = match value {
-                90 => direction.to_right(),
-                180 => direction.inverse(),
-                270 => direction.to_left(),
-                _ => panic!("moving right: need to refactor this to support more degrees"),
+    }
+
+    // Same as |apply|, except that only forward moves the ship, everything
+    // else moves the waypoint.
+    fn apply_with_waypoint(&mut self, action: &MoveAction) {
+        let waypoint = &mut self.waypoint;
+        match action {
+            MoveAction::North(value) => {
+                *waypoint = SYSTEM.advance(*waypoint, Direction::NORTH, *value)
             }
+            MoveAction::South(value) => {
+                *waypoint = SYSTEM.advance(*waypoint, Direction::SOUTH, *value)
+            }
+            MoveAction::East(value) => {
+                *waypoint = SYSTEM.advance(*waypoint, Direction::EAST, *value)
+            }
+            MoveAction::West(value) => {
+                *waypoint = SYSTEM.advance(*waypoint, Direction::WEST, *value)
+            }
+            MoveAction::Left(value) => SYSTEM
+                .rotate_left(waypoint, *value)
+                .expect(CHECKED_WHEN_PARSED),
+            MoveAction::Right(value) => SYSTEM
+                .rotate_right(waypoint, *value)
+                .expect(CHECKED_WHEN_PARSED),
+            MoveAction::Forward(value) => self.position += *waypoint * *value,
         }
-        MoveAction::Forward(value) => point.advance_mult(*direction, *value),
     }
-}
 
-fn apply_action_with_waypoint(action: &MoveAction, ship: &mut Point2D, waypoint: &mut Point2D) {
-    match action {
-        MoveAction::North(value) => waypoint.y += *value,
-        MoveAction::South(value) => waypoint.y -= *value,
-        MoveAction::East(value) => waypoint.x += *value,
-        MoveAction::West(value) => waypoint.x -= *value,
-        MoveAction::Left(value) => match value {
-            90 => waypoint.rotate_90_deg_left(),
-            180 => waypoint.rotate_180_deg(),
-            270 => waypoint.rotate_90_deg_right(),
-            _ => panic!("moving left: need to refactor this to support more degrees"),
-        },
-        MoveAction::Right(value) => match value {
-            90 => waypoint.rotate_90_deg_right(),
-            180 => waypoint.rotate_180_deg(),
-            270 => waypoint.rotate_90_deg_left(),
-            _ => panic!("moving right: need to refactor this to support more degrees"),
-        },
-        MoveAction::Forward(value) => *ship += waypoint.scale_copy(*value),
+    fn distance(&self) -> i64 {
+        self.position.manhattan_distance(&Point2D::default())
     }
-}
 
-fn part_one(actions: &[MoveAction]) -> i64 {
-    let mut point = Point2D::default();
-    // TODO: change direction to make sure East == Right. Right now East is
-    // Right in part two, but Left in part one. The code works fine since the
-    // answer is the manhattan distance anyway.
-    let mut direction = Direction::Left;
+    // The distance is the puzzle's answer, the rest tells where it came from.
+    fn answer(&self) -> Answer {
+        Answer::Text(format!("{} ({})", self.distance(), self))
+    }
+}
 
+fn navigate(actions: &[MoveAction]) -> Ship {
+    let mut ship = Ship::default();
     for action in actions {
-        apply_action(action, &mut point, &mut direction);
+        ship.apply(action);
     }
-
-    // TODO: point.magnitude?
-    point.x.abs() + point.y.abs()
+    ship
 }
 
-fn part_two(actions: &[MoveAction]) -> i64 {
-    let mut ship = Point2D::default();
-    let mut waypoint = Point2D { x: 10, y: 1 };
+fn navigate_with_waypoint(actions: &[MoveAction]) -> Ship {
+    let mut ship = Ship::default();
     for action in actions {
-        apply_action_with_waypoint(action, &mut ship, &mut waypoint);
+        ship.apply_with_waypoint(action);
     }
-    ship.x.abs() + ship.y.abs()
+    ship
+}
+
+fn part_one(actions: &[MoveAction]) -> Ship {
+    navigate(actions)
+}
+
+fn part_two(actions: &[MoveAction]) -> Ship {
+    navigate_with_waypoint(actions)
 }
 
-pub fn solve() -> String {
-    let actions = Vec::<MoveAction>::load("input/day_twelve.txt");
-    format!(
-        "part one: {}, part two: {}",
-        part_one(&actions),
-        part_two(&actions)
-    )
+pub struct DayTwelve;
+
+impl Solver for DayTwelve {
+    type Input = Vec<MoveAction>;
+
+    fn day(&self) -> u32 {
+        12
+    }
+
+    fn title(&self) -> &'static str {
+        "Rain Risk"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some("input/day_twelve.txt")
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Vec::<MoveAction>::try_load(filename)
+    }
+
+    fn part_one(&self, actions: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(actions).answer())
+    }
+
+    fn part_two(&self, actions: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(actions).answer())
+    }
 }
 
 #[cfg(test)]
@@ -130,18 +212,55 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 1424, part two: 63447", solve());
+        assert_eq!(
+            "part one: 1424 (at (507, -917) facing south), part two: 63447 (at (-24386, -39061) facing east)",
+            DayTwelve.solve()
+        );
     }
 
     #[test]
     fn test_example() {
         let matrix = Vec::<MoveAction>::load("input/day_twelve_example.txt");
-        assert_eq!(25, part_one(&matrix));
+        assert_eq!(
+            Answer::Text("25 (at (17, -8) facing south)".to_owned()),
+            DayTwelve.part_one(&matrix).unwrap()
+        );
+
+        // 17 east and 8 south.
+        let ship = part_one(&matrix);
+        assert_eq!(25, ship.distance());
+        assert_eq!(Point2D { x: 17, y: -8 }, ship.position);
+        assert_eq!(Direction::SOUTH, ship.heading);
     }
 
     #[test]
     fn test_example_part_two() {
         let matrix = Vec::<MoveAction>::load("input/day_twelve_example.txt");
-        assert_eq!(286, part_two(&matrix));
+        assert_eq!(
+            Answer::Text("286 (at (214, -72) facing east)".to_owned()),
+            DayTwelve.part_two(&matrix).unwrap()
+        );
+
+        // 214 east and 72 south, with the waypoint 4 east and 10 south.
+        let ship = part_two(&matrix);
+        assert_eq!(286, ship.distance());
+        assert_eq!(Point2D { x: 214, y: -72 }, ship.position);
+        assert_eq!(Point2D { x: 4, y: -10 }, ship.waypoint);
+        assert_eq!(Direction::EAST, ship.heading);
+    }
+
+    #[test]
+    fn test_turns() {
+        // Any multiple of 90 degrees works, in either direction.
+        let actions = Vec::<MoveAction>::load_str("F10\nL450\nF2\nR-180\nF1");
+        let ship = navigate(&actions);
+        assert_eq!(Point2D { x: 10, y: 1 }, ship.position);
+        assert_eq!(Direction::SOUTH, ship.heading);
+        assert_eq!(
+            "2:2: 45 degrees is not a multiple of 90",
+            Vec::<MoveAction>::try_load_str("F10\nR45")
+                .unwrap_err()
+                .to_string()
+        );
     }
 }
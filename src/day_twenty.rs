@@ -1,61 +1,150 @@
-use crate::loadable::LoadableFromFile;
-use crate::yet_another_geometry_mod::{Matrix2D, Point2D};
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
-
-// Note: tiles are 10x10
-// TODO: field is never read?? what??
-#[allow(dead_code)]
+use crate::error::Error;
+use crate::loadable::{paragraphs, LoadableFromFile};
+use crate::solver::{Answer, Solver};
+use crate::yet_another_geometry_mod::{Direction, Matrix2D, Point2D};
+
+// The puzzle input isn't bundled, so the example stands in for it.
+const INPUT_FILENAME: &str = "input/day_twenty_example.txt";
+const TILE_SIZE: usize = 10;
+
 #[derive(Debug)]
-struct Tile {
+pub struct Tile {
     id: i32,
     data: Matrix2D<char>,
 }
 
 impl LoadableFromFile for Vec<Tile> {
-    fn load(filename: &str) -> Self {
-        let file = File::open(filename).expect("Invalid filename");
-        let reader = BufReader::new(file);
-
-        let mut lines = reader.lines();
+    fn try_load_str(contents: &str) -> Result<Self, Error> {
+        // Every paragraph is a tile: a header, then its rows.
         let mut tiles = vec![];
-        while let Some(l) = lines.next() {
-            let tile_name = l.unwrap()[5..9].parse::<i32>().unwrap();
+        for paragraph in paragraphs(contents) {
+            let at_line = |e: Error| e.at_line(paragraph.first_line);
+            let id = paragraph.lines[0]
+                .strip_prefix("Tile ")
+                .and_then(|l| l.strip_suffix(':'))
+                .ok_or_else(|| at_line(Error::parse("expected a header like \"Tile 1234:\"")))?
+                .parse::<i32>()
+                .map_err(|e| at_line(Error::from(e).at_column("Tile ".len() + 1)))?;
+
+            let rows = &paragraph.lines[1..];
+            if rows.len() != TILE_SIZE {
+                return Err(Error::parse(format!(
+                    "tile {} has {} rows, expected {}",
+                    id,
+                    rows.len(),
+                    TILE_SIZE
+                ))
+                .at_line(paragraph.last_line()));
+            }
             let mut data = Vec::new();
-            for _ in 0..10 {
-                lines
-                    .next()
-                    .expect("valid")
-                    .unwrap()
-                    .chars()
-                    .for_each(|c| data.push(c))
+            for (number, row) in paragraph.numbered_lines().skip(1) {
+                if row.chars().count() != TILE_SIZE {
+                    return Err(
+                        Error::parse(format!("expected {} columns", TILE_SIZE)).at_line(number)
+                    );
+                }
+                data.extend(row.chars());
             }
-            // Grab empty line between tiles (or EOF).
-            lines.next();
 
             tiles.push(Tile {
-                id: tile_name,
+                id,
                 data: Matrix2D::<char> {
-                    data: data,
-                    size: Point2D { x: 10, y: 10 },
+                    data,
+                    size: Point2D {
+                        x: TILE_SIZE as i64,
+                        y: TILE_SIZE as i64,
+                    },
                 },
             });
         }
-        tiles
+        Ok(tiles)
     }
 }
 
-fn part_one() -> i64 {
-    0
+impl Tile {
+    // Every border in every orientation, so two tiles fit together when
+    // they have one in common.
+    fn borders(&self) -> Vec<String> {
+        self.data
+            .orientations()
+            .iter()
+            .flat_map(|tile| {
+                Direction::ALL
+                    .iter()
+                    .map(move |side| tile.border(*side).unwrap().iter().collect())
+            })
+            .collect()
+    }
+}
+
+// Ids of the tiles in the corners of the image: those with two borders that
+// don't fit any other tile. Borders are unique enough in the puzzle input
+// that nothing needs to be assembled to tell.
+fn corners(tiles: &[Tile]) -> Vec<i32> {
+    let borders: Vec<Vec<String>> = tiles.iter().map(Tile::borders).collect();
+    tiles
+        .iter()
+        .enumerate()
+        .filter(|(i, tile)| {
+            let unmatched = Direction::ALL
+                .iter()
+                .filter(|side| {
+                    let border: String = tile.data.border(**side).unwrap().iter().collect();
+                    !borders
+                        .iter()
+                        .enumerate()
+                        .any(|(j, other)| j != *i && other.contains(&border))
+                })
+                .count();
+            unmatched == 2
+        })
+        .map(|(_, tile)| tile.id)
+        .collect()
+}
+
+fn part_one(tiles: &[Tile]) -> Result<i64, Error> {
+    let corners = corners(tiles);
+    if corners.len() != 4 {
+        return Err(Error::parse(format!(
+            "expected 4 corner tiles, found {}",
+            corners.len()
+        )));
+    }
+    Ok(corners.iter().map(|id| *id as i64).product())
 }
 
 fn part_two() -> i64 {
     0
 }
 
-pub fn solve() -> String {
-    format!("part one: {}, part two: {}", part_one(), part_two())
+pub struct DayTwenty;
+
+impl Solver for DayTwenty {
+    type Input = Vec<Tile>;
+
+    fn day(&self) -> u32 {
+        20
+    }
+
+    fn title(&self) -> &'static str {
+        "Jurassic Jigsaw"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some(INPUT_FILENAME)
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Vec::<Tile>::try_load(filename)
+    }
+
+    fn part_one(&self, tiles: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(tiles)?.into())
+    }
+
+    fn part_two(&self, _input: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two().into())
+    }
 }
 
 #[cfg(test)]
@@ -64,14 +153,21 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 0, part two: 0", solve());
+        assert_eq!("part one: 20899048083289, part two: 0", DayTwenty.solve());
     }
 
     #[test]
     fn test_example() {
         let tiles = Vec::<Tile>::load("input/day_twenty_example.txt");
-        println!("tiles: {:?}", tiles);
-        // input/day_twenty_example.txt
-        // 1951 * 3079 * 2971 * 1171 = 20899048083289
+        let mut corners = corners(&tiles);
+        corners.sort_unstable();
+        assert_eq!(vec![1171, 1951, 2971, 3079], corners);
+        assert_eq!(20899048083289, part_one(&tiles).unwrap());
+
+        // Without the last tile its neighbours have unmatched borders too.
+        assert_eq!(
+            "expected 4 corner tiles, found 5",
+            part_one(&tiles[..tiles.len() - 1]).unwrap_err().to_string()
+        );
     }
 }
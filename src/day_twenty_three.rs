@@ -1,5 +1,43 @@
+use crate::error::Error;
+use crate::loadable::LoadableFromFile;
+use crate::solver::{Answer, Solver};
 use std::collections::VecDeque;
 
+const INPUT_FILENAME: &str = "input/day_twenty_three.txt";
+
+#[derive(Debug, PartialEq)]
+pub struct Cups(Vec<i64>);
+
+impl LoadableFromFile for Cups {
+    fn try_load_str(contents: &str) -> Result<Self, Error> {
+        // A single line with one digit per cup, in clockwise order.
+        let line = contents.trim();
+        let mut cups = Vec::with_capacity(line.len());
+        for (i, c) in line.chars().enumerate() {
+            match c.to_digit(10) {
+                Some(cup) if cup > 0 && !cups.contains(&i64::from(cup)) => {
+                    cups.push(i64::from(cup))
+                }
+                _ => {
+                    return Err(Error::parse(format!("'{}' is not a new cup label", c))
+                        .at_line(1)
+                        .at_column(i + 1))
+                }
+            }
+        }
+        // Every move picks up three cups and needs somewhere else to put
+        // them, and the labels have to be 1 up to the number of cups.
+        if cups.len() < 5 || cups.iter().max() != Some(&(cups.len() as i64)) {
+            return Err(Error::parse(format!(
+                "expected at least 5 cups labelled from 1 up, found {}",
+                line
+            ))
+            .at_line(1));
+        }
+        Ok(Cups(cups))
+    }
+}
+
 #[derive(Debug)]
 struct Game {
     current_cup_index: usize,
@@ -16,8 +54,8 @@ impl Game {
         Game {
             current_cup_index: 0,
             cups: VecDeque::from(cups),
-            min: min,
-            max: max,
+            min,
+            max,
         }
     }
 
@@ -46,7 +84,7 @@ impl Game {
     fn do_move(&mut self) {
         let current_cup_label = self.cups[self.current_cup_index];
 
-        let cups_to_move = vec![
+        let cups_to_move = [
             self.pop_cup(self.current_cup_index + 1),
             self.pop_cup(self.current_cup_index + 1),
             self.pop_cup(self.current_cup_index + 1),
@@ -85,43 +123,65 @@ impl Game {
         let one_index = self.cups.iter().position(|&c| c == 1).unwrap();
 
         let mut out: i64 = 0;
-        for i in one_index + 1..self.cups.len() {
+        for cup in self.cups.iter().skip(one_index + 1) {
             out *= 10;
-            out += self.cups[i];
+            out += cup;
         }
-        for i in 0..one_index {
+        for cup in self.cups.iter().take(one_index) {
             out *= 10;
-            out += self.cups[i];
+            out += cup;
         }
         out
     }
 }
 
-fn part_one() -> i64 {
-    let mut game = Game::from_vec(vec![9, 1, 6, 4, 3, 8, 2, 7, 5]);
+fn part_one(cups: &Cups) -> i64 {
+    let mut game = Game::from_vec(cups.0.clone());
     game.do_move_n(100);
     game.print()
 }
 
-fn _part_two() -> i64 {
+fn _part_two(cups: &Cups) -> i64 {
     const CAPACITY: usize = 1000000;
-    let mut cups = Vec::<i64>::with_capacity(CAPACITY);
-    cups.extend(vec![9, 1, 6, 5, 3, 8, 2, 7, 5]);
-    for i in cups.len()..CAPACITY {
-        cups.push(i as i64 + 1);
+    let mut all_cups = Vec::<i64>::with_capacity(CAPACITY);
+    all_cups.extend(cups.0.iter());
+    for i in all_cups.len()..CAPACITY {
+        all_cups.push(i as i64 + 1);
     }
 
-    let mut game = Game::from_vec(cups);
+    let mut game = Game::from_vec(all_cups);
     game.do_move_n(10000000);
     game.print()
 }
 
-pub fn solve() -> String {
-    format!(
-        "part one: {}, part two: {}",
-        part_one(),
-        0 /*part_two()*/
-    )
+pub struct DayTwentyThree;
+
+impl Solver for DayTwentyThree {
+    type Input = Cups;
+
+    fn day(&self) -> u32 {
+        23
+    }
+
+    fn title(&self) -> &'static str {
+        "Crab Cups"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some(INPUT_FILENAME)
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Cups::try_load(filename)
+    }
+
+    fn part_one(&self, cups: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(cups).into())
+    }
+
+    fn part_two(&self, _input: &Self::Input) -> Result<Answer, Error> {
+        Ok(Answer::Number(0 /*_part_two()*/))
+    }
 }
 
 #[cfg(test)]
@@ -130,16 +190,35 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 39564287, part two: 0", solve());
+        assert_eq!("part one: 39564287, part two: 0", DayTwentyThree.solve());
     }
 
     #[test]
     fn test_example() {
-        let mut game = Game::from_vec(vec![3, 8, 9, 1, 2, 5, 4, 6, 7]);
+        let cups = Cups::load("input/day_twenty_three_example.txt");
+        assert_eq!(Cups(vec![3, 8, 9, 1, 2, 5, 4, 6, 7]), cups);
+
+        let mut game = Game::from_vec(cups.0);
         game.do_move_n(10);
         assert_eq!(92658374, game.print());
 
         game.do_move_n(90);
         assert_eq!(67384529, game.print());
     }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "1:4: 'x' is not a new cup label",
+            Cups::try_load_str("389x25467").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "1:3: '8' is not a new cup label",
+            Cups::try_load_str("388125467").unwrap_err().to_string()
+        );
+        assert_eq!(
+            "1: expected at least 5 cups labelled from 1 up, found 3819",
+            Cups::try_load_str("3819").unwrap_err().to_string()
+        );
+    }
 }
@@ -1,27 +1,21 @@
-use crate::loadable::LoadableFromFile;
+use crate::error::Error;
+use crate::loadable::{paragraphs, LoadableFromFile};
+use crate::solver::{Answer, Solver};
 use std::collections::hash_map::DefaultHasher;
 use std::collections::HashSet;
 use std::collections::VecDeque;
-use std::fs::File;
 use std::hash::{Hash, Hasher};
-use std::io::prelude::*;
-use std::io::BufReader;
 
-#[derive(PartialEq, Copy, Clone, Debug)]
+#[derive(PartialEq, Copy, Clone, Debug, Default)]
 enum GameState {
+    #[default]
     Running,
     PlayerOneWon,
     PlayerTwoWon,
 }
 
-impl Default for GameState {
-    fn default() -> Self {
-        GameState::Running
-    }
-}
-
 #[derive(Default, Clone, Debug)]
-struct Game {
+pub struct Game {
     player_one_deck: VecDeque<i32>,
     player_two_deck: VecDeque<i32>,
     state: GameState,
@@ -37,23 +31,32 @@ impl Hash for Game {
 }
 
 impl LoadableFromFile for Game {
-    fn load(filename: &str) -> Self {
-        let file = File::open(filename).expect("Invalid filename");
-        let reader = BufReader::new(file);
-        let mut lines = reader.lines();
-
-        let mut game = Game::default();
-        assert_eq!("Player 1:", lines.next().unwrap().unwrap());
-        while let Ok(card_id) = lines.next().unwrap().unwrap_or_default().parse::<i32>() {
-            game.player_one_deck.push_back(card_id);
+    fn try_load_str(contents: &str) -> Result<Self, Error> {
+        // One paragraph per player, each a header followed by their deck.
+        let decks = paragraphs(contents);
+        if decks.len() != 2 {
+            return Err(Error::parse(format!(
+                "expected 2 decks separated by a blank line, found {}",
+                decks.len()
+            )));
         }
+        let parse_card = |l: &str| Ok(l.parse::<i32>()?);
 
-        assert_eq!("Player 2:", lines.next().unwrap().unwrap());
-        while let Some(l) = lines.next() {
-            game.player_two_deck
-                .push_back(l.unwrap().parse::<i32>().unwrap());
+        let game = Game {
+            player_one_deck: decks[0]
+                .strip_header("Player 1:")?
+                .parse_lines(parse_card)?
+                .into(),
+            player_two_deck: decks[1]
+                .strip_header("Player 2:")?
+                .parse_lines(parse_card)?
+                .into(),
+            ..Game::default()
+        };
+        if game.player_one_deck.is_empty() || game.player_two_deck.is_empty() {
+            return Err(Error::parse("expected two non-empty decks"));
         }
-        game
+        Ok(game)
     }
 }
 
@@ -125,23 +128,24 @@ impl Game {
 
             let one_played = self.player_one_deck.pop_front().unwrap();
             let two_played = self.player_two_deck.pop_front().unwrap();
-            let round_state;
             // We now determine the winner of this round through a subgame.
-            if one_played <= self.player_one_deck.len() as i32
+            let round_state = if one_played <= self.player_one_deck.len() as i32
                 && two_played <= self.player_two_deck.len() as i32
             {
-                let mut recurse_game = Game::default();
-                recurse_game.player_one_deck = self.player_one_deck.clone();
+                let mut recurse_game = Game {
+                    player_one_deck: self.player_one_deck.clone(),
+                    player_two_deck: self.player_two_deck.clone(),
+                    ..Default::default()
+                };
                 recurse_game.player_one_deck.truncate(one_played as usize);
-                recurse_game.player_two_deck = self.player_two_deck.clone();
                 recurse_game.player_two_deck.truncate(two_played as usize);
                 recurse_game.play_until_done_with_recursion();
-                round_state = recurse_game.state;
+                recurse_game.state
             } else if one_played > two_played {
-                round_state = GameState::PlayerOneWon;
+                GameState::PlayerOneWon
             } else {
-                round_state = GameState::PlayerTwoWon;
-            }
+                GameState::PlayerTwoWon
+            };
 
             if round_state == GameState::PlayerOneWon {
                 // winner takes the cards, with theirs first.
@@ -171,14 +175,34 @@ fn part_two(game: &mut Game) -> i64 {
     game.play_until_done_with_recursion()
 }
 
-pub fn solve() -> String {
-    let mut game = Game::load("input/day_twenty_two.txt");
-    let mut game_two = game.clone();
-    format!(
-        "part one: {}, part two: {}",
-        part_one(&mut game),
-        part_two(&mut game_two)
-    )
+pub struct DayTwentyTwo;
+
+impl Solver for DayTwentyTwo {
+    type Input = Game;
+
+    fn day(&self) -> u32 {
+        22
+    }
+
+    fn title(&self) -> &'static str {
+        "Crab Combat"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some("input/day_twenty_two.txt")
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Game::try_load(filename)
+    }
+
+    fn part_one(&self, game: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_one(&mut game.clone()).into())
+    }
+
+    fn part_two(&self, game: &Self::Input) -> Result<Answer, Error> {
+        Ok(part_two(&mut game.clone()).into())
+    }
 }
 
 #[cfg(test)]
@@ -213,6 +237,6 @@ mod tests {
 
     #[test]
     fn test_solve() {
-        assert_eq!("part one: 35818, part two: 34771", solve());
+        assert_eq!("part one: 35818, part two: 34771", DayTwentyTwo.solve());
     }
 }
@@ -1,25 +1,24 @@
-use crate::loadable::LoadableFromFile;
+use crate::error::Error;
+use crate::loadable::{parse_lines, LoadableFromFile};
+use crate::pattern::Pattern;
+use crate::solver::{Answer, Solver};
 use crate::validity::Validity;
-use lazy_static::lazy_static;
-use regex::Regex;
-use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
-use std::num::ParseIntError;
 use std::str::FromStr;
 
-const INPUT_FILENAME: &'static str = "input/day_two.txt";
+const INPUT_FILENAME: &str = "input/day_two.txt";
+const POLICY: Pattern = Pattern::new("{min}-{max} {letter}: {password}");
 
-struct PasswordAndPolicy {
-    min_count: i8,
-    max_count: i8,
+#[derive(Debug)]
+pub struct PasswordAndPolicy {
+    min_count: usize,
+    max_count: usize,
     required_letter: char,
     password: String,
 }
 
 impl Validity for PasswordAndPolicy {
     fn is_valid(&self) -> bool {
-        let count = self.password.matches(self.required_letter).count() as i8;
+        let count = self.password.matches(self.required_letter).count();
         self.min_count <= count && count <= self.max_count
     }
 }
@@ -30,58 +29,82 @@ trait NewValidity {
 
 impl NewValidity for PasswordAndPolicy {
     fn is_new_valid(&self) -> bool {
-        (self.password.as_bytes()[self.min_count as usize - 1] as char == self.required_letter)
-            != (self.password.as_bytes()[self.max_count as usize - 1] as char
-                == self.required_letter)
+        // Positions past the end of the password can't hold the letter.
+        let has_letter =
+            |position: usize| self.password.chars().nth(position - 1) == Some(self.required_letter);
+        has_letter(self.min_count) != has_letter(self.max_count)
     }
 }
 
 impl FromStr for PasswordAndPolicy {
-    type Err = ParseIntError;
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        lazy_static! {
-            // Cool note: compiling regex takes a LONG time, there are
-            // huge perf improvements by using a static here (100x faster).
-            static ref RE: Regex = Regex::new(r"^(\d+)-(\d+) ([a-z]): (.*)$").unwrap();
+        let captures = POLICY.captures(s)?;
+        let policy = PasswordAndPolicy {
+            min_count: captures.parse("min")?,
+            max_count: captures.parse("max")?,
+            required_letter: captures.parse("letter")?,
+            password: captures.get("password").to_owned(),
+        };
+        // Counts and positions alike start at 1. A max past the end of the
+        // password is still a fine count.
+        if policy.min_count == 0 {
+            return Err(
+                Error::parse("policy must start at 1 or more").at_column(captures.column("min"))
+            );
         }
-
-        let caps = RE.captures(s).unwrap();
-        Ok(PasswordAndPolicy {
-            min_count: caps[1].parse().unwrap(),
-            max_count: caps[2].parse().unwrap(),
-            required_letter: caps[3].parse().unwrap(),
-            password: caps[4].to_string(),
-        })
+        if policy.min_count > policy.max_count {
+            return Err(Error::parse(format!(
+                "policy range {}-{} is backwards",
+                policy.min_count, policy.max_count
+            ))
+            .at_column(captures.column("min")));
+        }
+        Ok(policy)
     }
 }
 
 impl LoadableFromFile for Vec<PasswordAndPolicy> {
-    fn load(filename: &str) -> Vec<PasswordAndPolicy> {
-        let file = File::open(filename).expect("Invalid filename");
-        let reader = BufReader::new(file);
-
-        reader
-            .lines()
-            .map(|r| match r {
-                Ok(n) => PasswordAndPolicy::from_str(&n).unwrap(),
-                Err(e) => panic!("{}", e),
-            })
-            .collect()
+    fn try_load_str(contents: &str) -> Result<Vec<PasswordAndPolicy>, Error> {
+        parse_lines(contents, PasswordAndPolicy::from_str)
     }
 }
 
-pub fn solve() -> String {
-    let passwords = Vec::<PasswordAndPolicy>::load(INPUT_FILENAME);
-    format!(
-        "part one: {}, part two: {}",
-        passwords
+pub struct DayTwo;
+
+impl Solver for DayTwo {
+    type Input = Vec<PasswordAndPolicy>;
+
+    fn day(&self) -> u32 {
+        2
+    }
+
+    fn title(&self) -> &'static str {
+        "Password Philosophy"
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Some(INPUT_FILENAME)
+    }
+
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error> {
+        Vec::<PasswordAndPolicy>::try_load(filename)
+    }
+
+    fn part_one(&self, passwords: &Self::Input) -> Result<Answer, Error> {
+        Ok(passwords
             .iter()
-            .fold(0, |sum, p| { sum + p.is_valid() as i32 }),
-        passwords
+            .fold(0, |sum, p| sum + p.is_valid() as i32)
+            .into())
+    }
+
+    fn part_two(&self, passwords: &Self::Input) -> Result<Answer, Error> {
+        Ok(passwords
             .iter()
-            .fold(0, |sum, p| { sum + p.is_new_valid() as i32 })
-    )
+            .fold(0, |sum, p| sum + p.is_new_valid() as i32)
+            .into())
+    }
 }
 
 #[cfg(test)]
@@ -90,6 +113,50 @@ mod tests {
 
     #[test]
     pub fn solves() {
-        assert_eq!("part one: 506, part two: 443", solve());
+        assert_eq!("part one: 506, part two: 443", DayTwo.solve());
+    }
+
+    #[test]
+    fn test_malformed_policies() {
+        let error = |s| {
+            Vec::<PasswordAndPolicy>::try_load_str(s)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            "2:3: invalid max: invalid digit found in string",
+            error("1-3 a: abcde\n1-x b: cdefg")
+        );
+        assert_eq!(
+            "1:5: invalid letter: too many characters in string",
+            error("1-3 ab: abcde")
+        );
+        assert_eq!("1:8: missing password", error("1-3 a: "));
+        assert_eq!(
+            "2:1: policy must start at 1 or more",
+            error("1-3 a: abcde\n0-3 a: abc")
+        );
+        assert_eq!("1:1: policy range 3-1 is backwards", error("3-1 a: abcde"));
+    }
+
+    #[test]
+    fn test_max_past_the_password() {
+        let passwords =
+            Vec::<PasswordAndPolicy>::load_str("1-9 a: abcde\n2-9 b: abcde\n1-9 b: abcde");
+        assert_eq!(Answer::Number(2), DayTwo.part_one(&passwords).unwrap());
+        // Only the first position can hold the letter.
+        assert_eq!(Answer::Number(2), DayTwo.part_two(&passwords).unwrap());
+    }
+
+    #[test]
+    fn test_long_passwords_and_other_letters() {
+        let passwords = Vec::<PasswordAndPolicy>::load_str(&format!(
+            "150-250 a: {}\n1-2 é: éb\n2-3 é: aéé",
+            "a".repeat(200)
+        ));
+        assert!(passwords[0].is_valid());
+        // Positions count letters, not bytes.
+        assert!(passwords[1].is_new_valid());
+        assert!(!passwords[2].is_new_valid());
     }
 }
@@ -0,0 +1,144 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::num::ParseIntError;
+
+#[derive(Debug)]
+pub enum Error {
+    // The input could not be read at all.
+    Io {
+        filename: String,
+        source: io::Error,
+    },
+
+    // The input was read, but is malformed. Parsers usually only know the
+    // reason (and maybe the column), the loaders calling them fill in the
+    // rest of the location as the error bubbles up.
+    Parse {
+        filename: Option<String>,
+        // Both line and column are 1-indexed, like an editor would show.
+        line: Option<usize>,
+        column: Option<usize>,
+        reason: String,
+    },
+}
+
+impl Error {
+    pub fn io(filename: &str, source: io::Error) -> Self {
+        Error::Io {
+            filename: filename.to_owned(),
+            source,
+        }
+    }
+
+    pub fn parse<S: Into<String>>(reason: S) -> Self {
+        Error::Parse {
+            filename: None,
+            line: None,
+            column: None,
+            reason: reason.into(),
+        }
+    }
+
+    // The location setters below never overwrite a more specific location
+    // that was set closer to where the error happened.
+    pub fn in_file(mut self, name: &str) -> Self {
+        if let Error::Parse { filename, .. } = &mut self {
+            filename.get_or_insert_with(|| name.to_owned());
+        }
+        self
+    }
+
+    pub fn at_line(mut self, number: usize) -> Self {
+        if let Error::Parse { line, .. } = &mut self {
+            line.get_or_insert(number);
+        }
+        self
+    }
+
+    pub fn at_column(mut self, number: usize) -> Self {
+        if let Error::Parse { column, .. } = &mut self {
+            column.get_or_insert(number);
+        }
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { filename, source } => write!(f, "{}: {}", filename, source),
+            Error::Parse {
+                filename,
+                line,
+                column,
+                reason,
+            } => {
+                // Formatted like compiler diagnostics, e.g. "foo.txt:3:7: reason".
+                if let Some(filename) = filename {
+                    write!(f, "{}:", filename)?;
+                }
+                if let Some(line) = line {
+                    write!(f, "{}:", line)?;
+                }
+                match (line, column) {
+                    (Some(_), Some(column)) => write!(f, "{}:", column)?,
+                    // A bare number would read as a line, so spell it out.
+                    (None, Some(column)) => write!(f, "column {}:", column)?,
+                    _ => (),
+                }
+                if filename.is_some() || line.is_some() || column.is_some() {
+                    write!(f, " ")?;
+                }
+                write!(f, "{}", reason)
+            }
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            Error::Parse { .. } => None,
+        }
+    }
+}
+
+impl From<ParseIntError> for Error {
+    fn from(err: ParseIntError) -> Self {
+        Error::parse(format!("invalid number: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        let err = Error::parse("bad thing").at_column(7).at_line(3);
+        assert_eq!("3:7: bad thing", err.to_string());
+        assert_eq!("foo.txt:3:7: bad thing", err.in_file("foo.txt").to_string());
+        assert_eq!("bad thing", Error::parse("bad thing").to_string());
+        assert_eq!(
+            "column 2: bad thing",
+            Error::parse("bad thing").at_column(2).to_string()
+        );
+    }
+
+    #[test]
+    fn test_location_is_not_overwritten() {
+        let err = Error::parse("bad thing").at_line(3).at_line(10);
+        assert_eq!("3: bad thing", err.to_string());
+    }
+
+    #[test]
+    fn test_from_parse_int_error() {
+        let err: Error = "x".parse::<i64>().unwrap_err().into();
+        assert_eq!(
+            "invalid number: invalid digit found in string",
+            err.to_string()
+        );
+    }
+}
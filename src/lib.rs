@@ -1,5 +1,9 @@
+pub mod answers;
+pub mod automaton;
+pub mod bench;
 pub mod christmas_day;
 pub mod christmas_eve;
+pub mod cli;
 pub mod day_eight;
 pub mod day_eighteen;
 pub mod day_eleven;
@@ -24,6 +28,11 @@ pub mod day_twenty_one;
 pub mod day_twenty_three;
 pub mod day_twenty_two;
 pub mod day_two;
+pub mod error;
 pub mod loadable;
+pub mod pattern;
+pub mod report;
+pub mod scaffold;
+pub mod solver;
 pub mod validity;
 pub mod yet_another_geometry_mod;
@@ -1,65 +1,430 @@
+use crate::error::Error;
+use crate::solver::STDIN_FILENAME;
 use crate::yet_another_geometry_mod::*;
-use std::fs;
+use flate2::read::GzDecoder;
+use std::convert::TryFrom;
+use std::fmt;
 use std::fs::File;
-use std::io::prelude::*;
-use std::io::BufReader;
-use std::str::FromStr;
+use std::io::{self, BufRead, BufReader, Read};
 
-pub trait LoadableFromFile {
-    fn load(filename: &str) -> Self;
+// Types that can be parsed from puzzle input. Implementors only parse from a
+// string, every other source (files, gzip compressed files, stdin or any
+// other reader) goes through that.
+pub trait LoadableFromFile: Sized {
+    // Errors carry the line and column, but no filename.
+    fn try_load_str(contents: &str) -> Result<Self, Error>;
+
+    // |name| is only used to describe the source in errors.
+    fn try_load_reader<R: BufRead>(mut reader: R, name: &str) -> Result<Self, Error> {
+        let mut contents = String::new();
+        reader
+            .read_to_string(&mut contents)
+            .map_err(|err| Error::io(name, err))?;
+        Self::try_load_str(&contents).map_err(|err| err.in_file(name))
+    }
+
+    // See |open| for the files that are understood.
+    fn try_load(filename: &str) -> Result<Self, Error> {
+        Self::try_load_reader(open(filename)?, display_name(filename))
+    }
+
+    // Conveniences for callers, mostly tests, that know their input is good.
+    fn load(filename: &str) -> Self {
+        Self::try_load(filename).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    fn load_str(contents: &str) -> Self {
+        Self::try_load_str(contents).unwrap_or_else(|err| panic!("{}", err))
+    }
 }
 
-impl LoadableFromFile for Vec<i64> {
-    fn load(filename: &str) -> Vec<i64> {
-        let contents = fs::read_to_string(filename).unwrap();
-        contents
-            .lines()
-            .map(|l| l.split(',').map(|s| i64::from_str(&s).unwrap()))
-            .flatten()
+fn display_name(filename: &str) -> &str {
+    if filename == STDIN_FILENAME {
+        "<stdin>"
+    } else {
+        filename
+    }
+}
+
+// Opens |filename| for reading: |STDIN_FILENAME| reads stdin, and files
+// ending in ".gz" are decompressed on the fly.
+pub fn open(filename: &str) -> Result<Box<dyn BufRead>, Error> {
+    if filename == STDIN_FILENAME {
+        return Ok(Box::new(BufReader::new(io::stdin())));
+    }
+    let file = File::open(filename).map_err(|err| Error::io(filename, err))?;
+    if filename.ends_with(".gz") {
+        Ok(Box::new(BufReader::new(GzDecoder::new(file))))
+    } else {
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+pub fn read_to_string(filename: &str) -> Result<String, Error> {
+    let mut contents = String::new();
+    open(filename)?
+        .read_to_string(&mut contents)
+        .map_err(|err| Error::io(display_name(filename), err))?;
+    Ok(contents)
+}
+
+// Parses every line of |contents| using |parse|, tagging any error with the
+// line it happened on.
+pub fn parse_lines<T, F>(contents: &str, mut parse: F) -> Result<Vec<T>, Error>
+where
+    F: FnMut(&str) -> Result<T, Error>,
+{
+    contents
+        .lines()
+        .enumerate()
+        .map(|(i, l)| parse(l).map_err(|err| err.at_line(i + 1)))
+        .collect()
+}
+
+// Same as |parse_lines|, but reads the lines from |filename|.
+pub fn load_lines<T, F>(filename: &str, parse: F) -> Result<Vec<T>, Error>
+where
+    F: FnMut(&str) -> Result<T, Error>,
+{
+    parse_lines(&read_to_string(filename)?, parse)
+        .map_err(|err| err.in_file(display_name(filename)))
+}
+
+// A run of non-blank lines, as used by inputs that separate their records
+// (or sections) with blank lines.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Paragraph<'a> {
+    // 1-indexed line number of the first line.
+    pub first_line: usize,
+    pub lines: Vec<&'a str>,
+}
+
+impl<'a> Paragraph<'a> {
+    pub fn last_line(&self) -> usize {
+        self.first_line + self.lines.len() - 1
+    }
+
+    // Every line, along with its 1-indexed line number.
+    pub fn numbered_lines(&self) -> impl Iterator<Item = (usize, &'a str)> + '_ {
+        (self.first_line..).zip(self.lines.iter().copied())
+    }
+
+    // Checks that the first line is exactly |header|, returning the rest.
+    pub fn strip_header(&self, header: &str) -> Result<Paragraph<'a>, Error> {
+        if self.lines[0] != header {
+            return Err(Error::parse(format!("expected \"{}\"", header)).at_line(self.first_line));
+        }
+        Ok(Paragraph {
+            first_line: self.first_line + 1,
+            lines: self.lines[1..].to_vec(),
+        })
+    }
+
+    // Parses every line using |parse|, tagging any error with the line it
+    // happened on.
+    pub fn parse_lines<T, F>(&self, mut parse: F) -> Result<Vec<T>, Error>
+    where
+        F: FnMut(&str) -> Result<T, Error>,
+    {
+        self.numbered_lines()
+            .map(|(number, l)| parse(l).map_err(|err| err.at_line(number)))
             .collect()
     }
 }
 
+// Splits |contents| on blank (or whitespace only) lines. Runs of blank
+// lines, including leading and trailing ones, never produce empty
+// paragraphs. Both LF and CRLF line endings are accepted.
+pub fn paragraphs(contents: &str) -> Vec<Paragraph<'_>> {
+    let mut paragraphs = Vec::new();
+    let mut current: Option<Paragraph> = None;
+    for (i, line) in contents.lines().enumerate() {
+        // |lines| only strips a carriage return that is followed by a newline.
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if line.trim().is_empty() {
+            paragraphs.extend(current.take());
+            continue;
+        }
+        current
+            .get_or_insert_with(|| Paragraph {
+                first_line: i + 1,
+                lines: Vec::new(),
+            })
+            .lines
+            .push(line);
+    }
+    paragraphs.extend(current);
+    paragraphs
+}
+
+impl LoadableFromFile for Vec<i64> {
+    fn try_load_str(contents: &str) -> Result<Vec<i64>, Error> {
+        let lines = parse_lines(contents, |l| {
+            let mut column = 1;
+            let mut numbers = Vec::new();
+            for s in l.split(',') {
+                numbers.push(
+                    s.parse::<i64>()
+                        .map_err(|e| Error::from(e).at_column(column))?,
+                );
+                column += s.len() + 1;
+            }
+            Ok(numbers)
+        })?;
+        Ok(lines.into_iter().flatten().collect())
+    }
+}
+
 impl LoadableFromFile for Vec<String> {
-    fn load(filename: &str) -> Vec<String> {
-        let contents = fs::read_to_string(filename).unwrap();
-        contents.lines().map(|l| l.to_owned()).collect()
+    fn try_load_str(contents: &str) -> Result<Vec<String>, Error> {
+        parse_lines(contents, |l| Ok(l.to_owned()))
     }
 }
 
-impl LoadableFromFile for Matrix2D<char> {
-    fn load(filename: &str) -> Matrix2D<char> {
-        let file = File::open(filename).expect("Invalid filename");
-        let reader = BufReader::new(file);
+// What |parse_grid| does with rows that are shorter than the longest row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaggedRows<T> {
+    Reject,
+    // Fills in the missing cells at the end of the row.
+    Pad(T),
+}
+
+// Parses a grid with one row per line and one cell per character (not
+// byte), converting each character with |T::try_from|. Trailing blank lines
+// are ignored, any other blank line is a row like any other.
+pub fn parse_grid<T>(contents: &str, ragged: RaggedRows<T>) -> Result<Matrix2D<T>, Error>
+where
+    T: TryFrom<char> + Clone,
+    T::Error: fmt::Display,
+{
+    let mut rows = Vec::new();
+    for (i, line) in contents.lines().enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        let row = line
+            .chars()
+            .enumerate()
+            .map(|(j, c)| {
+                T::try_from(c).map_err(|err| {
+                    Error::parse(err.to_string())
+                        .at_column(j + 1)
+                        .at_line(i + 1)
+                })
+            })
+            .collect::<Result<Vec<T>, Error>>()?;
+        rows.push(row);
+    }
+    while rows.last().is_some_and(|row| row.is_empty()) {
+        rows.pop();
+    }
 
-        let mut row_count: i64 = 0;
-        let mut column_count: i64 = 0;
-        let mut data = Vec::new();
-        for line in reader.lines() {
-            if let Ok(l) = line {
-                if column_count == 0 {
-                    column_count = l.len() as i64;
+    let (width, height) = (
+        rows.iter().map(Vec::len).max().unwrap_or_default(),
+        rows.len(),
+    );
+    let mut data = Vec::with_capacity(width * height);
+    for (i, mut row) in rows.into_iter().enumerate() {
+        if row.len() < width {
+            match &ragged {
+                RaggedRows::Reject => {
+                    return Err(Error::parse(format!(
+                        "expected {} columns like the longest row, found {}",
+                        width,
+                        row.len()
+                    ))
+                    .at_line(i + 1))
                 }
-                row_count += 1;
-                l.as_bytes().iter().for_each(|b| {
-                    data.push(*b as char);
-                });
+                RaggedRows::Pad(fill) => row.resize(width, fill.clone()),
             }
         }
+        data.extend(row);
+    }
 
-        Matrix2D::<char> {
-            data: data,
-            size: Point2D {
-                x: column_count,
-                y: row_count,
-            },
-        }
+    Ok(Matrix2D {
+        data,
+        size: Point2D {
+            x: width as i64,
+            y: height as i64,
+        },
+    })
+}
+
+impl<T> LoadableFromFile for Matrix2D<T>
+where
+    T: TryFrom<char> + Clone,
+    T::Error: fmt::Display,
+{
+    fn try_load_str(contents: &str) -> Result<Matrix2D<T>, Error> {
+        parse_grid(contents, RaggedRows::Reject)
     }
 }
 
 impl LoadableFromFile for i64 {
-    fn load(filename: &str) -> Self {
-        let data = fs::read_to_string(filename).expect("Unable to read file");
-        data.trim().parse().unwrap()
+    fn try_load_str(contents: &str) -> Result<Self, Error> {
+        Ok(contents.trim().parse()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_file() {
+        let err = Vec::<String>::try_load("input/does_not_exist.txt").unwrap_err();
+        assert!(matches!(err, Error::Io { .. }));
+        assert!(err.to_string().starts_with("input/does_not_exist.txt: "));
+    }
+
+    #[test]
+    fn test_load_str() {
+        assert_eq!(vec![1, 2, 3], Vec::<i64>::load_str("1,2\n3"));
+        assert_eq!(
+            "2:3: invalid number: invalid digit found in string",
+            Vec::<i64>::try_load_str("1,2\n3,x")
+                .unwrap_err()
+                .to_string()
+        );
+
+        let matrix = Matrix2D::<char>::load_str("#.\n.#\n");
+        assert_eq!(Point2D { x: 2, y: 2 }, matrix.size);
+        assert_eq!(vec!['#', '.', '.', '#'], matrix.data);
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Cell {
+        Wall,
+        Open,
+    }
+
+    impl TryFrom<char> for Cell {
+        type Error = String;
+
+        fn try_from(c: char) -> Result<Self, Self::Error> {
+            match c {
+                '#' => Ok(Cell::Wall),
+                '.' => Ok(Cell::Open),
+                _ => Err(format!("unexpected '{}' in grid", c)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_parse_grid() {
+        // Cells are characters, not bytes, and CRLF line endings are fine.
+        let matrix = Matrix2D::<char>::load_str("é.\r\n.ü\r\n\r\n");
+        assert_eq!(Point2D { x: 2, y: 2 }, matrix.size);
+        assert_eq!(vec!['é', '.', '.', 'ü'], matrix.data);
+
+        let matrix = Matrix2D::<Cell>::load_str("#.\n.#");
+        assert_eq!(
+            vec![Cell::Wall, Cell::Open, Cell::Open, Cell::Wall],
+            matrix.data
+        );
+        assert_eq!(
+            "2:2: unexpected 'x' in grid",
+            Matrix2D::<Cell>::try_load_str("#.\n.x")
+                .unwrap_err()
+                .to_string()
+        );
+
+        let ragged = "#.#\n.\n#.";
+        assert_eq!(
+            "2: expected 3 columns like the longest row, found 1",
+            Matrix2D::<Cell>::try_load_str(ragged)
+                .unwrap_err()
+                .to_string()
+        );
+        let padded = parse_grid(ragged, RaggedRows::Pad(Cell::Open)).unwrap();
+        assert_eq!(Point2D { x: 3, y: 3 }, padded.size);
+        assert_eq!(Cell::Open, padded.data[5]);
+
+        assert_eq!(Point2D::default(), Matrix2D::<char>::load_str("").size);
+    }
+
+    #[test]
+    fn test_load_reader() {
+        let reader = io::Cursor::new("12\n");
+        assert_eq!(12, i64::try_load_reader(reader, "memory").unwrap());
+
+        let reader = io::Cursor::new("twelve");
+        assert_eq!(
+            "memory: invalid number: invalid digit found in string",
+            i64::try_load_reader(reader, "memory")
+                .unwrap_err()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn test_load_gzip() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let filename = std::env::temp_dir().join("advent_of_code_test_load.txt.gz");
+        let filename = filename.to_str().unwrap();
+        let mut encoder = GzEncoder::new(File::create(filename).unwrap(), Compression::default());
+        encoder.write_all(b"nop +0\nacc +1\n").unwrap();
+        encoder.finish().unwrap();
+
+        assert_eq!(
+            vec!["nop +0".to_owned(), "acc +1".to_owned()],
+            Vec::<String>::load(filename)
+        );
+        std::fs::remove_file(filename).unwrap();
+    }
+
+    #[test]
+    fn test_paragraphs() {
+        let found = paragraphs("\n\na\nb\n\n  \n\nc\r\nd\r\n\r\n\r\n");
+        assert_eq!(
+            vec![
+                Paragraph {
+                    first_line: 3,
+                    lines: vec!["a", "b"]
+                },
+                Paragraph {
+                    first_line: 8,
+                    lines: vec!["c", "d"]
+                },
+            ],
+            found
+        );
+        assert_eq!(9, found[1].last_line());
+        assert_eq!(
+            vec![(8, "c"), (9, "d")],
+            found[1].numbered_lines().collect::<Vec<_>>()
+        );
+        assert_eq!(
+            "9: bad",
+            found[1]
+                .parse_lines(|l| if l == "d" {
+                    Err(Error::parse("bad"))
+                } else {
+                    Ok(())
+                })
+                .unwrap_err()
+                .to_string()
+        );
+
+        let body = found[0].strip_header("a").unwrap();
+        assert_eq!(vec![(4, "b")], body.numbered_lines().collect::<Vec<_>>());
+        assert_eq!(
+            "8: expected \"a\"",
+            found[1].strip_header("a").unwrap_err().to_string()
+        );
+
+        assert!(paragraphs("").is_empty());
+        assert!(paragraphs("\n \n").is_empty());
+        assert_eq!(1, paragraphs("no trailing newline").len());
+    }
+
+    #[test]
+    fn test_malformed_numbers() {
+        let err = Vec::<i64>::try_load("input/day_eight_example.txt").unwrap_err();
+        assert_eq!(
+            "input/day_eight_example.txt:1:1: invalid number: invalid digit found in string",
+            err.to_string()
+        );
     }
 }
@@ -0,0 +1,247 @@
+use crate::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+// A line format like "{min}-{max} {letter}: {password}", where every
+// "{name}" captures text and everything else has to match exactly. A field
+// captures everything up to the first occurrence of the text that follows
+// it, or the rest of the line if it comes last, and is never empty.
+//
+// Patterns are written by us rather than read from input, so a malformed one
+// (an unclosed brace, two fields in a row, or a repeated name) panics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pattern {
+    pattern: &'static str,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Piece {
+    Literal(&'static str),
+    Field(&'static str),
+}
+
+// The text captured by each field of a |Pattern|, along with where it was
+// found so that errors point at the offending field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Captures<'a> {
+    // (name, 1-indexed column, text) in pattern order.
+    fields: Vec<(&'static str, usize, &'a str)>,
+}
+
+impl Pattern {
+    pub const fn new(pattern: &'static str) -> Pattern {
+        Pattern { pattern }
+    }
+
+    fn pieces(&self) -> Vec<Piece> {
+        let mut pieces = Vec::new();
+        let mut rest = self.pattern;
+        while !rest.is_empty() {
+            match rest.find('{') {
+                Some(0) => {
+                    let end = rest
+                        .find('}')
+                        .unwrap_or_else(|| panic!("unclosed field in \"{}\"", self.pattern));
+                    let name = &rest[1..end];
+                    if let Some(Piece::Field(_)) = pieces.last() {
+                        panic!("fields need text between them in \"{}\"", self.pattern);
+                    }
+                    if pieces.contains(&Piece::Field(name)) {
+                        panic!("field {} is repeated in \"{}\"", name, self.pattern);
+                    }
+                    pieces.push(Piece::Field(name));
+                    rest = &rest[end + 1..];
+                }
+                Some(start) => {
+                    pieces.push(Piece::Literal(&rest[..start]));
+                    rest = &rest[start..];
+                }
+                None => {
+                    pieces.push(Piece::Literal(rest));
+                    rest = "";
+                }
+            }
+        }
+        pieces
+    }
+
+    fn mismatch(&self, column: usize) -> Error {
+        Error::parse(format!("expected \"{}\"", self.pattern)).at_column(column)
+    }
+
+    pub fn captures<'a>(&self, s: &'a str) -> Result<Captures<'a>, Error> {
+        self.captures_at(s, 1)
+    }
+
+    // Same as |captures|, for when |s| is only part of a line that starts at
+    // |column|, so errors still point at the right place in the line.
+    pub fn captures_at<'a>(&self, s: &'a str, column: usize) -> Result<Captures<'a>, Error> {
+        let pieces = self.pieces();
+        let mut fields = Vec::new();
+        let mut position = 0;
+        for (i, piece) in pieces.iter().enumerate() {
+            let rest = &s[position..];
+            match piece {
+                Piece::Literal(literal) => {
+                    if !rest.starts_with(literal) {
+                        return Err(self.mismatch(column + position));
+                    }
+                    position += literal.len();
+                }
+                Piece::Field(name) => {
+                    let length = match pieces.get(i + 1) {
+                        Some(Piece::Literal(next)) => rest
+                            .find(next)
+                            .ok_or_else(|| self.mismatch(column + position))?,
+                        _ => rest.len(),
+                    };
+                    if length == 0 {
+                        return Err(
+                            Error::parse(format!("missing {}", name)).at_column(column + position)
+                        );
+                    }
+                    fields.push((*name, column + position, &rest[..length]));
+                    position += length;
+                }
+            }
+        }
+        if position != s.len() {
+            return Err(self.mismatch(column + position));
+        }
+        Ok(Captures { fields })
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.pattern)
+    }
+}
+
+impl<'a> Captures<'a> {
+    fn field(&self, name: &str) -> (usize, &'a str) {
+        self.fields
+            .iter()
+            .find(|(n, _, _)| *n == name)
+            .map(|(_, column, text)| (*column, *text))
+            .unwrap_or_else(|| panic!("no field named {}", name))
+    }
+
+    pub fn get(&self, name: &str) -> &'a str {
+        self.field(name).1
+    }
+
+    // 1-indexed column the field starts at.
+    pub fn column(&self, name: &str) -> usize {
+        self.field(name).0
+    }
+
+    // Parses the field with |FromStr|, e.g. `captures.parse::<i64>("min")`.
+    pub fn parse<T>(&self, name: &str) -> Result<T, Error>
+    where
+        T: FromStr,
+        T::Err: fmt::Display,
+    {
+        self.parse_with(name, |text| {
+            text.parse::<T>()
+                .map_err(|err| Error::parse(format!("invalid {}: {}", name, err)))
+        })
+    }
+
+    // Parses the field with |parse|, pointing any error at the field.
+    pub fn parse_with<T, F>(&self, name: &str, parse: F) -> Result<T, Error>
+    where
+        F: FnOnce(&'a str) -> Result<T, Error>,
+    {
+        let (column, text) = self.field(name);
+        parse(text).map_err(|err| err.at_column(column))
+    }
+}
+
+// Splits |s| on |separator| like |str::split|, but also yields the
+// 1-indexed column each part starts at.
+pub fn split_with_columns<'a>(
+    s: &'a str,
+    separator: &'a str,
+) -> impl Iterator<Item = (usize, &'a str)> + 'a {
+    let mut column = 1;
+    s.split(separator).map(move |part| {
+        let start = column;
+        column += part.len() + separator.len();
+        (start, part)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POLICY: Pattern = Pattern::new("{min}-{max} {letter}: {password}");
+
+    #[test]
+    fn test_captures() {
+        let captures = POLICY.captures("1-13 a: abcde").unwrap();
+        assert_eq!(1, captures.parse::<i64>("min").unwrap());
+        assert_eq!(13, captures.parse::<i64>("max").unwrap());
+        assert_eq!('a', captures.parse::<char>("letter").unwrap());
+        assert_eq!("abcde", captures.get("password"));
+        assert_eq!(9, captures.column("password"));
+
+        // Fields stop at the first match of the text after them.
+        let captures = Pattern::new("{name}: {value}").captures("a: b: c").unwrap();
+        assert_eq!(("a", "b: c"), (captures.get("name"), captures.get("value")));
+
+        let captures = Pattern::new("mem[{address}] = {value}")
+            .captures("mem[8] = 11")
+            .unwrap();
+        assert_eq!(8, captures.parse::<usize>("address").unwrap());
+    }
+
+    #[test]
+    fn test_errors() {
+        let error = |s| POLICY.captures(s).unwrap_err().to_string();
+        assert_eq!(
+            "column 1: expected \"{min}-{max} {letter}: {password}\"",
+            error("1 3 a: abcde")
+        );
+        assert_eq!("column 8: missing password", error("1-3 a: "));
+        assert_eq!("column 1: missing min", error("-3 a: abcde"));
+        assert_eq!(
+            "column 3: invalid max: invalid digit found in string",
+            POLICY
+                .captures("1-x a: abcde")
+                .and_then(|c| c.parse::<i64>("max"))
+                .unwrap_err()
+                .to_string()
+        );
+        assert_eq!(
+            "column 5: too big",
+            POLICY
+                .captures_at("1-3 a: abcde", 5)
+                .and_then(|c| c.parse_with("min", |_| Err::<(), _>(Error::parse("too big"))))
+                .unwrap_err()
+                .to_string()
+        );
+
+        // Trailing text is not silently ignored.
+        let error = Pattern::new("mem[{address}]").captures("mem[8] = 1");
+        assert_eq!(
+            "column 7: expected \"mem[{address}]\"",
+            error.unwrap_err().to_string()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "fields need text between them")]
+    fn test_adjacent_fields() {
+        let _ = Pattern::new("{a}{b}").captures("ab");
+    }
+
+    #[test]
+    fn test_split_with_columns() {
+        assert_eq!(
+            vec![(1, "ab"), (5, "c"), (8, "")],
+            split_with_columns("ab, c, ", ", ").collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,463 @@
+use crate::solver::{resolve_input, Answer, DynSolver, Part};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    // Coloured, human readable output.
+    Text,
+    // One JSON object per line.
+    Json,
+    // A header row followed by one row per record.
+    Csv,
+}
+
+impl Format {
+    pub fn parse(s: &str) -> Option<Format> {
+        match s {
+            "text" => Some(Format::Text),
+            "json" => Some(Format::Json),
+            "csv" => Some(Format::Csv),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Ok,
+    // The input could not be read or parsed.
+    Error(String),
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Status::Ok => write!(f, "ok"),
+            Status::Error(_) => write!(f, "error"),
+        }
+    }
+}
+
+// The outcome of solving a single part of a day. Both parts of a day share
+// the same parse, so they report the same |parse_time|.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub day: u32,
+    pub part: Part,
+    pub answer: Option<Answer>,
+    pub parse_time: Duration,
+    pub part_time: Duration,
+    pub status: Status,
+}
+
+fn part_number(part: Part) -> u32 {
+    match part {
+        Part::One => 1,
+        Part::Two => 2,
+    }
+}
+
+// Parses |input| once and solves the selected |part|, or both parts if
+// None, timing each phase separately.
+pub fn solve_timed(solver: &dyn DynSolver, input: Option<&str>, part: Option<Part>) -> Vec<Record> {
+    let parts = match part {
+        Some(part) => vec![part],
+        None => vec![Part::One, Part::Two],
+    };
+
+    let start = Instant::now();
+    let parsed = solver.parse(&resolve_input(solver, input));
+    let parse_time = start.elapsed();
+
+    parts
+        .into_iter()
+        .map(|part| {
+            let mut record = Record {
+                day: solver.day(),
+                part,
+                answer: None,
+                parse_time,
+                part_time: Duration::default(),
+                status: Status::Ok,
+            };
+            match &parsed {
+                Ok(parsed) => {
+                    let start = Instant::now();
+                    let answer = match part {
+                        Part::One => solver.part_one(parsed.as_ref()),
+                        Part::Two => solver.part_two(parsed.as_ref()),
+                    };
+                    record.part_time = start.elapsed();
+                    match answer {
+                        Ok(answer) => record.answer = Some(answer),
+                        Err(err) => record.status = Status::Error(err.to_string()),
+                    }
+                }
+                Err(err) => record.status = Status::Error(err.to_string()),
+            }
+            record
+        })
+        .collect()
+}
+
+// Why the day went wrong, if it did: bad input fails every part, while a
+// part can also fail on its own.
+pub fn first_error(records: &[Record]) -> Option<&str> {
+    records.iter().find_map(|r| match &r.status {
+        Status::Error(reason) => Some(reason.as_str()),
+        Status::Ok => None,
+    })
+}
+
+// Time spent on a whole day: the shared parse is only counted once.
+pub fn total_time(records: &[Record]) -> Duration {
+    records.first().map(|r| r.parse_time).unwrap_or_default()
+        + records.iter().map(|r| r.part_time).sum::<Duration>()
+}
+
+// Formats |duration| with a unit that keeps a few significant digits, from
+// nanoseconds up to seconds, e.g. "512ns", "3.25µs" or "1.50s".
+pub fn format_duration(duration: Duration) -> String {
+    let nanos = duration.as_nanos();
+    let (value, unit) = if nanos < 1_000 {
+        return format!("{}ns", nanos);
+    } else if nanos < 1_000_000 {
+        (nanos as f64 / 1e3, "µs")
+    } else if nanos < 1_000_000_000 {
+        (nanos as f64 / 1e6, "ms")
+    } else {
+        (nanos as f64 / 1e9, "s")
+    };
+    let precision = if value < 10.0 {
+        2
+    } else if value < 100.0 {
+        1
+    } else {
+        0
+    };
+    format!("{:.*}{}", precision, value, unit)
+}
+
+// Solves every one of |solvers| using up to |jobs| threads. |emit| is
+// called with the records of each day in the order of |solvers|, as soon
+// as that day and every day before it are done. Returning false from
+// |emit| stops solving any further days.
+pub fn solve_in_parallel<F>(
+    solvers: &[&dyn DynSolver],
+    input: Option<&str>,
+    part: Option<Part>,
+    jobs: usize,
+    mut emit: F,
+) where
+    F: FnMut(Vec<Record>) -> bool,
+{
+    let next = AtomicUsize::new(0);
+    let (sender, receiver) = mpsc::channel();
+    thread::scope(|scope| {
+        for _ in 0..jobs.clamp(1, solvers.len().max(1)) {
+            let sender = sender.clone();
+            let next = &next;
+            scope.spawn(move || loop {
+                let index = next.fetch_add(1, Ordering::SeqCst);
+                if index >= solvers.len() {
+                    break;
+                }
+                let records = solve_timed(solvers[index], input, part);
+                if sender.send((index, records)).is_err() {
+                    break;
+                }
+            });
+        }
+        // Otherwise the receiver below would wait for this sender forever.
+        drop(sender);
+
+        // Days that finished before an earlier one are held back.
+        let mut pending = BTreeMap::new();
+        let mut next_to_emit = 0;
+        for (index, records) in receiver {
+            pending.insert(index, records);
+            while let Some(records) = pending.remove(&next_to_emit) {
+                if !emit(records) {
+                    // Dropping the receiver tells the workers to stop.
+                    return;
+                }
+                next_to_emit += 1;
+            }
+        }
+    });
+}
+
+fn json_string(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len() + 2);
+    escaped.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// Fields containing separators, quotes or newlines are quoted, as per
+// RFC 4180.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_owned()
+    }
+}
+
+// The last two columns are only filled in on the totals row.
+pub const CSV_HEADER: &str = "day,part,answer,parse_ns,part_ns,status,error,wall_ns,summed_day_ns";
+
+impl Record {
+    pub fn to_json(&self) -> String {
+        let answer = match &self.answer {
+            Some(Answer::Number(n)) => n.to_string(),
+            Some(Answer::Text(s)) => json_string(s),
+            None => "null".to_owned(),
+        };
+        let error = match &self.status {
+            Status::Ok => "null".to_owned(),
+            Status::Error(reason) => json_string(reason),
+        };
+        format!(
+            "{{\"day\":{},\"part\":{},\"answer\":{},\"parse_ns\":{},\"part_ns\":{},\"status\":\"{}\",\"error\":{}}}",
+            self.day,
+            part_number(self.part),
+            answer,
+            self.parse_time.as_nanos(),
+            self.part_time.as_nanos(),
+            self.status,
+            error
+        )
+    }
+
+    // Matches the columns of |CSV_HEADER|.
+    pub fn to_csv(&self) -> String {
+        let answer = self
+            .answer
+            .as_ref()
+            .map(|a| csv_field(&a.to_string()))
+            .unwrap_or_default();
+        let error = match &self.status {
+            Status::Ok => String::new(),
+            Status::Error(reason) => csv_field(reason),
+        };
+        format!(
+            "{},{},{},{},{},{},{},,",
+            self.day,
+            part_number(self.part),
+            answer,
+            self.parse_time.as_nanos(),
+            self.part_time.as_nanos(),
+            self.status,
+            error
+        )
+    }
+}
+
+// Timings of a whole run. The days are timed one by one, so with several
+// jobs their summed time can be longer than the wall time of the run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Totals {
+    pub wall_time: Duration,
+    pub summed_day_time: Duration,
+}
+
+impl Totals {
+    pub fn add_day(&mut self, records: &[Record]) {
+        self.summed_day_time += total_time(records);
+    }
+
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"total\":{{\"wall_ns\":{},\"summed_day_ns\":{}}}}}",
+            self.wall_time.as_nanos(),
+            self.summed_day_time.as_nanos()
+        )
+    }
+
+    // Matches the columns of |CSV_HEADER|, with "total" in place of a day.
+    pub fn to_csv(&self) -> String {
+        format!(
+            "total,,,,,,,{},{}",
+            self.wall_time.as_nanos(),
+            self.summed_day_time.as_nanos()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::solver::{Registry, Solver};
+
+    fn record(answer: Option<Answer>, status: Status) -> Record {
+        Record {
+            day: 8,
+            part: Part::Two,
+            answer,
+            parse_time: Duration::from_nanos(1500),
+            part_time: Duration::from_nanos(20),
+            status,
+        }
+    }
+
+    #[test]
+    fn test_json() {
+        assert_eq!(
+            r#"{"day":8,"part":2,"answer":1056,"parse_ns":1500,"part_ns":20,"status":"ok","error":null}"#,
+            record(Some(Answer::Number(1056)), Status::Ok).to_json()
+        );
+        assert_eq!(
+            r#"{"day":8,"part":2,"answer":null,"parse_ns":1500,"part_ns":20,"status":"error","error":"a \"bad\"\nthing"}"#,
+            record(None, Status::Error("a \"bad\"\nthing".to_owned())).to_json()
+        );
+    }
+
+    #[test]
+    fn test_csv() {
+        assert_eq!(
+            "8,2,1056,1500,20,ok,,,",
+            record(Some(Answer::Number(1056)), Status::Ok).to_csv()
+        );
+        assert_eq!(
+            "8,2,\"a,b\",1500,20,error,\"x:1: \"\"y\"\"\",,",
+            record(
+                Some(Answer::Text("a,b".to_owned())),
+                Status::Error("x:1: \"y\"".to_owned())
+            )
+            .to_csv()
+        );
+    }
+
+    #[test]
+    fn test_totals() {
+        let mut totals = Totals {
+            wall_time: Duration::from_nanos(1000),
+            ..Totals::default()
+        };
+        totals.add_day(&[record(None, Status::Ok), record(None, Status::Ok)]);
+        totals.add_day(&[record(None, Status::Ok)]);
+        assert_eq!(Duration::from_nanos(3060), totals.summed_day_time);
+        assert_eq!(
+            r#"{"total":{"wall_ns":1000,"summed_day_ns":3060}}"#,
+            totals.to_json()
+        );
+        assert_eq!("total,,,,,,,1000,3060", totals.to_csv());
+        assert_eq!(
+            CSV_HEADER.matches(',').count(),
+            totals.to_csv().matches(',').count()
+        );
+    }
+
+    #[test]
+    fn test_format_duration() {
+        assert_eq!("0ns", format_duration(Duration::default()));
+        assert_eq!("999ns", format_duration(Duration::from_nanos(999)));
+        assert_eq!("3.25µs", format_duration(Duration::from_nanos(3_250)));
+        assert_eq!("12.5ms", format_duration(Duration::from_micros(12_500)));
+        assert_eq!("150ms", format_duration(Duration::from_millis(150)));
+        assert_eq!("1.50s", format_duration(Duration::from_millis(1_500)));
+    }
+
+    #[test]
+    fn test_total_time() {
+        let records = vec![record(None, Status::Ok), record(None, Status::Ok)];
+        assert_eq!(Duration::from_nanos(1540), total_time(&records));
+        assert_eq!(Duration::default(), total_time(&[]));
+    }
+
+    #[test]
+    fn test_solve_in_parallel() {
+        let registry = Registry::default();
+        let solvers: Vec<_> = [10, 1, 8, 5, 3]
+            .iter()
+            .map(|day| registry.get(*day).unwrap())
+            .collect();
+        let mut days = vec![];
+        solve_in_parallel(&solvers, None, Some(Part::One), 3, |records| {
+            assert_eq!(1, records.len());
+            assert_eq!(Status::Ok, records[0].status);
+            days.push(records[0].day);
+            true
+        });
+        assert_eq!(vec![10, 1, 8, 5, 3], days);
+
+        let mut count = 0;
+        solve_in_parallel(&solvers, None, None, 2, |_| {
+            count += 1;
+            count < 2
+        });
+        assert_eq!(2, count);
+    }
+
+    #[test]
+    fn test_solve_timed() {
+        let registry = Registry::default();
+        let day_eight = registry.get(8).unwrap();
+
+        let records = solve_timed(day_eight, None, None);
+        assert_eq!(2, records.len());
+        assert_eq!(Some(Answer::Number(1814)), records[0].answer);
+        assert_eq!(Some(Answer::Number(1056)), records[1].answer);
+        assert_eq!(records[0].parse_time, records[1].parse_time);
+
+        let records = solve_timed(day_eight, Some("input/day_one.txt"), Some(Part::One));
+        assert_eq!(1, records.len());
+        assert_eq!(None, records[0].answer);
+        assert!(matches!(records[0].status, Status::Error(_)));
+        assert!(first_error(&records).is_some());
+    }
+
+    struct Unsolvable;
+
+    impl Solver for Unsolvable {
+        type Input = ();
+
+        fn day(&self) -> u32 {
+            1
+        }
+
+        fn title(&self) -> &'static str {
+            "Unsolvable"
+        }
+
+        fn parse(&self, _filename: &str) -> Result<(), Error> {
+            Ok(())
+        }
+
+        fn part_one(&self, _input: &()) -> Result<Answer, Error> {
+            Ok(Answer::Number(1))
+        }
+
+        fn part_two(&self, _input: &()) -> Result<Answer, Error> {
+            Err(Error::parse("no solution"))
+        }
+    }
+
+    #[test]
+    fn test_solve_timed_part_error() {
+        let records = solve_timed(&Unsolvable, None, None);
+        assert_eq!(Some(Answer::Number(1)), records[0].answer);
+        assert_eq!(Status::Ok, records[0].status);
+        assert_eq!(None, records[1].answer);
+        assert_eq!(Status::Error("no solution".to_owned()), records[1].status);
+        assert_eq!(Some("no solution"), first_error(&records));
+    }
+}
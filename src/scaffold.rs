@@ -0,0 +1,513 @@
+use crate::answers;
+use crate::error::Error;
+use crate::loadable::read_to_string;
+use crate::solver::{DEFAULT_YEAR, FIRST_DAY, LAST_DAY};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const TEMPLATE_FILENAME: &str = "src/day_template.rs";
+const TEMPLATE_MODULE: &str = "day_template";
+const TEMPLATE_STRUCT: &str = "DayTemplate";
+const TEMPLATE_TITLE: &str = "\"Template\"";
+const TEMPLATE_DAY: &str = "fn day(&self) -> u32 {\n        0\n    }";
+const TEMPLATE_INPUT: &str = "\"input/day_template";
+// Where |registry_for_year| lists the years added by new-day.
+const YEARS_MARKER: &str = "// The new-day command adds every other year below.";
+
+const NUMBERS: [&str; 23] = [
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+    "twenty",
+    "twenty_one",
+    "twenty_two",
+    "twenty_three",
+];
+
+// Module name of a day, e.g. "day_twenty_one". The last two are special.
+pub fn module_name(day: u32) -> String {
+    assert!((FIRST_DAY..=LAST_DAY).contains(&day), "no such day {}", day);
+    match day {
+        24 => "christmas_eve".to_owned(),
+        25 => "christmas_day".to_owned(),
+        _ => format!("day_{}", NUMBERS[day as usize - 1]),
+    }
+}
+
+// Module holding the days of |year|, e.g. "year_2021". Days of
+// |DEFAULT_YEAR| live at the top of src instead.
+pub fn year_module(year: u32) -> String {
+    format!("year_{}", year)
+}
+
+// Where the inputs of |year| go, relative to the root of the repository.
+fn input_dir(year: u32) -> String {
+    if year == DEFAULT_YEAR {
+        "input".to_owned()
+    } else {
+        format!("input/{}", year)
+    }
+}
+
+// Name of the solver struct in a module, e.g. "day_twenty_one" is solved by
+// "DayTwentyOne".
+pub fn struct_name(module: &str) -> String {
+    module
+        .split('_')
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn day_of_module(module: &str) -> Option<u32> {
+    (FIRST_DAY..=LAST_DAY).find(|day| module_name(*day) == module)
+}
+
+fn missing_from_template(what: &str) -> Error {
+    Error::parse(format!("template is missing {}", what)).in_file(TEMPLATE_FILENAME)
+}
+
+// Fills in the template for |day| of |year|, checking that everything that
+// needs replacing is actually there.
+pub fn render_module(template: &str, year: u32, day: u32, title: &str) -> Result<String, Error> {
+    let module = module_name(day);
+    for what in &[
+        TEMPLATE_MODULE,
+        TEMPLATE_STRUCT,
+        TEMPLATE_TITLE,
+        TEMPLATE_DAY,
+        TEMPLATE_INPUT,
+    ] {
+        if !template.contains(what) {
+            return Err(missing_from_template(what));
+        }
+    }
+    Ok(template
+        .replace(TEMPLATE_DAY, &TEMPLATE_DAY.replace('0', &day.to_string()))
+        .replace(TEMPLATE_TITLE, &format!("{:?}", title))
+        .replace(TEMPLATE_INPUT, &format!("\"{}/{}", input_dir(year), module))
+        .replace(TEMPLATE_STRUCT, &struct_name(&module))
+        .replace(TEMPLATE_MODULE, &module))
+}
+
+// Adds "pub mod |module|;" to |lib| (lib.rs or a year module), keeping the
+// declarations sorted. The first one goes right after the imports.
+pub fn add_module_declaration(lib: &str, module: &str) -> String {
+    let declaration = format!("pub mod {};", module);
+    let mut lines: Vec<&str> = lib.lines().collect();
+    let is_declaration = |l: &&str| l.starts_with("pub mod ");
+    let position = match lines.iter().rposition(is_declaration) {
+        Some(last) => lines
+            .iter()
+            .position(|l| is_declaration(l) && *l > declaration.as_str())
+            .unwrap_or(last + 1),
+        None => {
+            let imports = lines
+                .iter()
+                .rposition(|l| l.starts_with("use "))
+                .map_or(0, |i| i + 1);
+            lines.insert(imports, "");
+            imports + 1
+        }
+    };
+    lines.insert(position, &declaration);
+    lines.join("\n") + "\n"
+}
+
+// A module for a year without any days yet, see |year_module|.
+pub fn new_year_module(year: u32) -> String {
+    format!(
+        "use crate::solver::Registry;
+
+// Every day of {}, registered.
+pub fn registry() -> Registry {{
+    let mut registry = Registry::new();
+    registry
+}}
+",
+        year
+    )
+}
+
+// Makes |registry_for_year| in solver.rs return the registry of |year|,
+// keeping the years in order.
+pub fn add_year(solver: &str, year: u32) -> Result<String, Error> {
+    let arm = format!(
+        "        {} => Some({}::registry()),",
+        year,
+        year_module(year)
+    );
+    let mut lines: Vec<&str> = solver.lines().collect();
+    let marker = lines
+        .iter()
+        .position(|l| l.trim() == YEARS_MARKER)
+        .ok_or_else(|| Error::parse(format!("missing \"{}\"", YEARS_MARKER)))?;
+    let listed_year = |l: &str| {
+        l.trim()
+            .split_once(" => Some(year_")
+            .and_then(|(year, _)| year.parse::<u32>().ok())
+    };
+    let mut position = marker + 1;
+    while lines
+        .get(position)
+        .and_then(|l| listed_year(l))
+        .is_some_and(|y| y < year)
+    {
+        position += 1;
+    }
+    lines.insert(position, &arm);
+    Ok(lines.join("\n") + "\n")
+}
+
+// Registers the solver for |day| in a registry (the default one in
+// solver.rs, or a year module's), keeping the registrations in day order.
+// The first one goes right after the registry is created.
+pub fn add_registration(solver: &str, day: u32) -> Result<String, Error> {
+    let module = module_name(day);
+    let mut lines: Vec<&str> = solver.lines().collect();
+    let registered_day = |l: &str| {
+        l.trim()
+            .strip_prefix("registry.register(")
+            .and_then(|l| l.split("::").next())
+            .and_then(day_of_module)
+    };
+    if lines.iter().any(|l| registered_day(l) == Some(day)) {
+        return Err(Error::parse(format!("day {} is already registered", day)));
+    }
+    let last = lines
+        .iter()
+        .rposition(|l| registered_day(l).is_some())
+        .or_else(|| lines.iter().position(|l| l.contains("Registry::new()")))
+        .ok_or_else(|| Error::parse("no registry found"))?;
+    let indent = &lines[last][..lines[last].len() - lines[last].trim_start().len()];
+    let registration = format!(
+        "{}registry.register({}::{});",
+        indent,
+        module,
+        struct_name(&module)
+    );
+    let position = lines
+        .iter()
+        .position(|l| registered_day(l).is_some_and(|d| d > day))
+        .unwrap_or(last + 1);
+    lines.insert(position, &registration);
+    Ok(lines.join("\n") + "\n")
+}
+
+pub fn answers_entry(day: u32) -> String {
+    format!(
+        "\n[day.{}]\n# part_one = <answer>\n# part_two = <answer>\n",
+        day
+    )
+}
+
+fn write(path: &Path, contents: &str) -> Result<(), Error> {
+    fs::write(path, contents).map_err(|err| Error::io(&path.display().to_string(), err))
+}
+
+fn read(path: &Path) -> Result<String, Error> {
+    read_to_string(&path.display().to_string())
+}
+
+fn create_dir(path: &Path) -> Result<(), Error> {
+    fs::create_dir_all(path).map_err(|err| Error::io(&path.display().to_string(), err))
+}
+
+// Generates the module for |day| of |year| under |root| from the day
+// template, then declares it, registers it, and adds empty inputs and an
+// answers entry. Days of |DEFAULT_YEAR| go at the top of src, every other
+// year gets a module of its own the first time one of its days is added.
+// Returns every file that was created or changed.
+pub fn new_day(root: &Path, year: u32, day: u32, title: &str) -> Result<Vec<PathBuf>, Error> {
+    let module = module_name(day);
+    let src = if year == DEFAULT_YEAR {
+        root.join("src")
+    } else {
+        root.join("src").join(year_module(year))
+    };
+    let module_path = src.join(format!("{}.rs", module));
+    if module_path.exists() {
+        return Err(Error::parse(format!(
+            "{} already exists",
+            module_path.display()
+        )));
+    }
+
+    let template = read(&root.join(TEMPLATE_FILENAME))?;
+    let rendered = render_module(&template, year, day, title)?;
+    let in_file = |path: &Path| {
+        let filename = path.display().to_string();
+        move |err: Error| err.in_file(&filename)
+    };
+
+    // Each file to write along with its new contents. The day is declared
+    // in lib.rs and registered in solver.rs, or both in its year module.
+    let mut updates = Vec::new();
+    let lib_path = root.join("src/lib.rs");
+    let solver_path = root.join("src/solver.rs");
+    if year == DEFAULT_YEAR {
+        let lib = add_module_declaration(&read(&lib_path)?, &module);
+        let solver = add_registration(&read(&solver_path)?, day).map_err(in_file(&solver_path))?;
+        updates.push((lib_path, lib));
+        updates.push((solver_path, solver));
+    } else {
+        let year_path = src.join("mod.rs");
+        let year_source = if year_path.exists() {
+            read(&year_path)?
+        } else {
+            let lib = add_module_declaration(&read(&lib_path)?, &year_module(year));
+            let solver = add_year(&read(&solver_path)?, year).map_err(in_file(&solver_path))?;
+            updates.push((lib_path, lib));
+            updates.push((solver_path, solver));
+            new_year_module(year)
+        };
+        let year_source = add_registration(&add_module_declaration(&year_source, &module), day)
+            .map_err(in_file(&year_path))?;
+        updates.insert(0, (year_path, year_source));
+    }
+
+    // Only start writing once everything that can fail to parse has.
+    let mut changed = Vec::new();
+    create_dir(&src)?;
+    write(&module_path, &rendered)?;
+    changed.push(module_path);
+    for (path, contents) in updates {
+        write(&path, &contents)?;
+        changed.push(path);
+    }
+
+    let inputs = root.join(input_dir(year));
+    create_dir(&inputs)?;
+    for input in &[format!("{}.txt", module), format!("{}_example.txt", module)] {
+        let path = inputs.join(input);
+        if !path.exists() {
+            write(&path, "")?;
+            changed.push(path);
+        }
+    }
+
+    let answers_path = root.join(answers::default_filename(year));
+    let answers = if answers_path.exists() {
+        read(&answers_path)?
+    } else {
+        String::new()
+    };
+    if !answers.contains(&format!("[day.{}]", day)) {
+        write(&answers_path, &(answers + &answers_entry(day)))?;
+        changed.push(answers_path);
+    }
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_names() {
+        assert_eq!("day_one", module_name(1));
+        assert_eq!("day_twenty_three", module_name(23));
+        assert_eq!("christmas_eve", module_name(24));
+        assert_eq!("DayTwentyThree", struct_name("day_twenty_three"));
+        assert_eq!("ChristmasDay", struct_name(&module_name(25)));
+        assert_eq!(Some(11), day_of_module("day_eleven"));
+        assert_eq!(None, day_of_module("day_template"));
+    }
+
+    #[test]
+    fn test_render_bundled_template() {
+        let template = read_to_string(TEMPLATE_FILENAME).unwrap();
+        let rendered = render_module(&template, DEFAULT_YEAR, 12, "Rain Risk").unwrap();
+        assert!(rendered.contains("pub struct DayTwelve;"));
+        assert!(rendered.contains("fn day(&self) -> u32 {\n        12\n    }"));
+        assert!(rendered.contains("\"Rain Risk\""));
+        assert!(rendered.contains("\"input/day_twelve_example.txt\""));
+        assert!(!rendered.contains("emplate"));
+        let rendered = render_module(&template, 2021, 1, "Sonar Sweep").unwrap();
+        assert!(rendered.contains("\"input/2021/day_one.txt\""));
+        assert!(rendered.contains("\"input/2021/day_one_example.txt\""));
+
+        assert!(render_module("pub struct DayTemplate;", DEFAULT_YEAR, 12, "Rain Risk").is_err());
+    }
+
+    #[test]
+    fn test_add_module_declaration() {
+        let lib = "pub mod cli;\npub mod day_eight;\npub mod solver;\n";
+        assert_eq!(
+            "pub mod cli;\npub mod day_eight;\npub mod day_five;\npub mod solver;\n",
+            add_module_declaration(lib, "day_five")
+        );
+        assert_eq!(
+            "pub mod cli;\npub mod day_eight;\npub mod solver;\npub mod validity;\n",
+            add_module_declaration(lib, "validity")
+        );
+        assert_eq!(
+            "use crate::solver::Registry;\n\npub mod day_one;\n\npub fn registry() {}\n",
+            add_module_declaration(
+                "use crate::solver::Registry;\n\npub fn registry() {}",
+                "day_one"
+            )
+        );
+    }
+
+    #[test]
+    fn test_add_year() {
+        let solver = format!(
+            "    match year {{\n        {}\n        2022 => Some(year_2022::registry()),\n        _ => None,\n",
+            YEARS_MARKER
+        );
+        let solver = add_year(&solver, 2021).unwrap();
+        let solver = add_year(&solver, 2023).unwrap();
+        assert_eq!(
+            format!(
+                "    match year {{\n        {}\n        2021 => Some(year_2021::registry()),\n        \
+                 2022 => Some(year_2022::registry()),\n        2023 => Some(year_2023::registry()),\n        \
+                 _ => None,\n",
+                YEARS_MARKER
+            ),
+            solver
+        );
+        assert!(add_year("fn main() {}", 2021).is_err());
+    }
+
+    #[test]
+    fn test_add_registration() {
+        let solver = "        let mut registry = Registry::new();
+        registry.register(day_one::DayOne);
+        registry.register(day_three::DayThree);
+        registry
+";
+        assert_eq!(
+            "        let mut registry = Registry::new();
+        registry.register(day_one::DayOne);
+        registry.register(day_two::DayTwo);
+        registry.register(day_three::DayThree);
+        registry
+",
+            add_registration(solver, 2).unwrap()
+        );
+        assert!(add_registration(solver, 25)
+            .unwrap()
+            .contains("DayThree);\n        registry.register(christmas_day::ChristmasDay);\n"));
+        assert!(add_registration("fn main() {}", 2).is_err());
+        assert!(add_registration(&new_year_module(2021), 5)
+            .unwrap()
+            .contains(
+                "Registry::new();\n    registry.register(day_five::DayFive);\n    registry\n"
+            ));
+    }
+
+    #[test]
+    fn test_new_day() {
+        let root = std::env::temp_dir().join("advent_of_code_test_new_day");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("input")).unwrap();
+        fs::copy(TEMPLATE_FILENAME, root.join(TEMPLATE_FILENAME)).unwrap();
+        fs::write(root.join("src/lib.rs"), "pub mod day_one;\n").unwrap();
+        fs::write(
+            root.join("src/solver.rs"),
+            "        registry.register(day_one::DayOne);\n",
+        )
+        .unwrap();
+
+        let changed = new_day(&root, DEFAULT_YEAR, 2, "Password Philosophy").unwrap();
+        assert_eq!(6, changed.len());
+        assert!(fs::read_to_string(root.join("src/day_two.rs"))
+            .unwrap()
+            .contains("\"Password Philosophy\""));
+        assert_eq!(
+            "pub mod day_one;\npub mod day_two;\n",
+            fs::read_to_string(root.join("src/lib.rs")).unwrap()
+        );
+        assert!(root.join("input/day_two_example.txt").exists());
+        assert!(fs::read_to_string(root.join("answers.toml"))
+            .unwrap()
+            .contains("[day.2]"));
+
+        // Days are never overwritten.
+        assert!(new_day(&root, DEFAULT_YEAR, 2, "Password Philosophy").is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_new_day_of_another_year() {
+        // The real lib.rs and solver.rs, where every day of the default
+        // year is taken.
+        let root = std::env::temp_dir().join("advent_of_code_test_new_year");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src")).unwrap();
+        for file in &[TEMPLATE_FILENAME, "src/lib.rs", "src/solver.rs"] {
+            fs::copy(file, root.join(file)).unwrap();
+        }
+        // Even without its module, a registered day is taken.
+        assert_eq!(
+            format!(
+                "{}: day 12 is already registered",
+                root.join("src/solver.rs").display()
+            ),
+            new_day(&root, DEFAULT_YEAR, 12, "Rain Risk")
+                .unwrap_err()
+                .to_string()
+        );
+
+        let changed = new_day(&root, 2021, 2, "Dive!").unwrap();
+        assert_eq!(7, changed.len());
+        let changed = new_day(&root, 2021, 1, "Sonar Sweep").unwrap();
+        // Only the module, the year module, the inputs and the answers.
+        assert_eq!(5, changed.len());
+
+        let read = |path: &str| fs::read_to_string(root.join(path)).unwrap();
+        assert!(read("src/year_2021/day_one.rs").contains("pub struct DayOne;"));
+        assert!(read("src/year_2021/day_one.rs").contains("\"input/2021/day_one.txt\""));
+        assert_eq!(
+            "use crate::solver::Registry;
+
+pub mod day_one;
+pub mod day_two;
+
+// Every day of 2021, registered.
+pub fn registry() -> Registry {
+    let mut registry = Registry::new();
+    registry.register(day_one::DayOne);
+    registry.register(day_two::DayTwo);
+    registry
+}
+",
+            read("src/year_2021/mod.rs")
+        );
+        assert!(read("src/lib.rs").contains("pub mod validity;\npub mod year_2021;\n"));
+        assert_eq!(1, read("src/lib.rs").matches("year_2021").count());
+        assert!(read("src/solver.rs").contains(&format!(
+            "{}\n        2021 => Some(year_2021::registry()),\n        _ => None,",
+            YEARS_MARKER
+        )));
+        assert!(root.join("input/2021/day_two_example.txt").exists());
+        assert!(read("answers_2021.toml").contains("[day.1]"));
+        assert!(!root.join("answers.toml").exists());
+
+        assert!(new_day(&root, 2021, 1, "Sonar Sweep").is_err());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,426 @@
+use crate::automaton::Experiment;
+use crate::error::Error;
+use crate::*;
+use std::any::Any;
+use std::fmt;
+
+// Answers are almost always integers, but some puzzles (e.g. ones that spell
+// out letters) want a string instead.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Number(i64),
+    Text(String),
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Number(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+impl From<i64> for Answer {
+    fn from(n: i64) -> Self {
+        Answer::Number(n)
+    }
+}
+
+impl From<i32> for Answer {
+    fn from(n: i32) -> Self {
+        Answer::Number(n.into())
+    }
+}
+
+impl From<usize> for Answer {
+    fn from(n: usize) -> Self {
+        Answer::Number(n as i64)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Text(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Text(s.to_owned())
+    }
+}
+
+// Passing this as the input filename reads the puzzle input from stdin, see
+// |loadable::open|.
+pub const STDIN_FILENAME: &str = "-";
+
+// The calendar runs from the first to Christmas Day.
+pub const FIRST_DAY: u32 = 1;
+pub const LAST_DAY: u32 = 25;
+
+// The year whose days live at the top of src. Every other year gets a
+// year_<YEAR> module of its own, see |registry_for_year|.
+pub const DEFAULT_YEAR: u32 = 2020;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl fmt::Display for Part {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Part::One => write!(f, "part one"),
+            Part::Two => write!(f, "part two"),
+        }
+    }
+}
+
+// Human readable name for a day, the last two are special.
+pub fn day_name(day: u32) -> String {
+    match day {
+        24 => "Christmas Eve".to_owned(),
+        25 => "Christmas Day".to_owned(),
+        _ => format!("Day {}", day),
+    }
+}
+
+pub trait Solver {
+    type Input;
+
+    fn day(&self) -> u32;
+    fn title(&self) -> &'static str;
+    fn parse(&self, filename: &str) -> Result<Self::Input, Error>;
+    // Parts can fail on input that parsed fine but can't be solved, e.g. a
+    // bad rule for an automaton.
+    fn part_one(&self, input: &Self::Input) -> Result<Answer, Error>;
+    fn part_two(&self, input: &Self::Input) -> Result<Answer, Error>;
+
+    // The bundled input for this day, if the day has one. Some days have
+    // their (small) input baked into the code instead.
+    fn default_input(&self) -> Option<&'static str> {
+        None
+    }
+
+    // Days whose |part| runs a cellular automaton can run it as an
+    // |experiment| instead, see the automaton command. None for every other
+    // part.
+    fn run_automaton(
+        &self,
+        _input: &Self::Input,
+        _part: Part,
+        _experiment: &Experiment,
+    ) -> Option<Result<Answer, Error>> {
+        None
+    }
+
+    fn solve(&self) -> String {
+        let input = self
+            .parse(self.default_input().unwrap_or_default())
+            .unwrap_or_else(|err| panic!("{}", err));
+        let solve = |answer: Result<Answer, Error>| answer.unwrap_or_else(|err| panic!("{}", err));
+        format!(
+            "part one: {}, part two: {}",
+            solve(self.part_one(&input)),
+            solve(self.part_two(&input))
+        )
+    }
+}
+
+// Type erased version of |Solver|, so that days with different input types
+// can live in the same registry. Parsed input is handed back as an opaque
+// box that is only valid for the solver that produced it.
+pub trait DynSolver: Send + Sync {
+    fn day(&self) -> u32;
+    fn title(&self) -> &'static str;
+    fn default_input(&self) -> Option<&'static str>;
+    fn parse(&self, filename: &str) -> Result<Box<dyn Any>, Error>;
+    fn part_one(&self, input: &dyn Any) -> Result<Answer, Error>;
+    fn part_two(&self, input: &dyn Any) -> Result<Answer, Error>;
+    fn run_automaton(
+        &self,
+        input: &dyn Any,
+        part: Part,
+        experiment: &Experiment,
+    ) -> Option<Result<Answer, Error>>;
+    fn solve(&self) -> String;
+}
+
+fn downcast<S: Solver>(input: &dyn Any) -> &S::Input
+where
+    S::Input: 'static,
+{
+    input
+        .downcast_ref::<S::Input>()
+        .expect("input was parsed by a different solver")
+}
+
+impl<S> DynSolver for S
+where
+    S: Solver + Send + Sync,
+    S::Input: 'static,
+{
+    fn day(&self) -> u32 {
+        Solver::day(self)
+    }
+
+    fn title(&self) -> &'static str {
+        Solver::title(self)
+    }
+
+    fn default_input(&self) -> Option<&'static str> {
+        Solver::default_input(self)
+    }
+
+    fn parse(&self, filename: &str) -> Result<Box<dyn Any>, Error> {
+        Ok(Box::new(Solver::parse(self, filename)?))
+    }
+
+    fn part_one(&self, input: &dyn Any) -> Result<Answer, Error> {
+        Solver::part_one(self, downcast::<S>(input))
+    }
+
+    fn part_two(&self, input: &dyn Any) -> Result<Answer, Error> {
+        Solver::part_two(self, downcast::<S>(input))
+    }
+
+    fn run_automaton(
+        &self,
+        input: &dyn Any,
+        part: Part,
+        experiment: &Experiment,
+    ) -> Option<Result<Answer, Error>> {
+        Solver::run_automaton(self, downcast::<S>(input), part, experiment)
+    }
+
+    fn solve(&self) -> String {
+        Solver::solve(self)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    pub day: u32,
+    pub part_one: Answer,
+    pub part_two: Answer,
+}
+
+// Resolves which file a solver should actually read: an explicit |input|
+// wins over the bundled default.
+pub fn resolve_input(solver: &dyn DynSolver, input: Option<&str>) -> String {
+    input
+        .or_else(|| solver.default_input())
+        .unwrap_or_default()
+        .to_owned()
+}
+
+// Parses |input| (or the bundled input if None) and runs both parts on it.
+pub fn run(solver: &dyn DynSolver, input: Option<&str>) -> Result<Solution, Error> {
+    let parsed = solver.parse(&resolve_input(solver, input))?;
+    Ok(Solution {
+        day: solver.day(),
+        part_one: solver.part_one(parsed.as_ref())?,
+        part_two: solver.part_two(parsed.as_ref())?,
+    })
+}
+
+// Same as |run|, but only solves a single part.
+pub fn run_part(solver: &dyn DynSolver, input: Option<&str>, part: Part) -> Result<Answer, Error> {
+    let parsed = solver.parse(&resolve_input(solver, input))?;
+    match part {
+        Part::One => solver.part_one(parsed.as_ref()),
+        Part::Two => solver.part_two(parsed.as_ref()),
+    }
+}
+
+pub struct Registry {
+    solvers: Vec<Box<dyn DynSolver>>,
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry {
+            solvers: Vec::new(),
+        }
+    }
+
+    pub fn register<S>(&mut self, solver: S)
+    where
+        S: Solver + Send + Sync + 'static,
+        S::Input: 'static,
+    {
+        assert!(
+            self.get(Solver::day(&solver)).is_none(),
+            "day {} registered twice",
+            Solver::day(&solver)
+        );
+        self.solvers.push(Box::new(solver));
+        self.solvers.sort_by_key(|s| s.day());
+    }
+
+    pub fn get(&self, day: u32) -> Option<&dyn DynSolver> {
+        self.solvers
+            .iter()
+            .find(|s| s.day() == day)
+            .map(|s| s.as_ref())
+    }
+
+    // Solvers are always iterated in day order.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn DynSolver> {
+        self.solvers.iter().map(|s| s.as_ref())
+    }
+
+    pub fn len(&self) -> usize {
+        self.solvers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.solvers.is_empty()
+    }
+}
+
+impl Default for Registry {
+    // Every day of the calendar, registered.
+    fn default() -> Self {
+        let mut registry = Registry::new();
+        registry.register(day_one::DayOne);
+        registry.register(day_two::DayTwo);
+        registry.register(day_three::DayThree);
+        registry.register(day_four::DayFour);
+        registry.register(day_five::DayFive);
+        registry.register(day_six::DaySix);
+        registry.register(day_seven::DaySeven);
+        registry.register(day_eight::DayEight);
+        registry.register(day_nine::DayNine);
+        registry.register(day_ten::DayTen);
+        registry.register(day_eleven::DayEleven);
+        registry.register(day_twelve::DayTwelve);
+        registry.register(day_thirteen::DayThirteen);
+        registry.register(day_fourteen::DayFourteen);
+        registry.register(day_fifteen::DayFifteen);
+        registry.register(day_sixteen::DaySixteen);
+        registry.register(day_seventeen::DaySeventeen);
+        registry.register(day_eighteen::DayEighteen);
+        registry.register(day_nineteen::DayNineteen);
+        registry.register(day_twenty::DayTwenty);
+        registry.register(day_twenty_one::DayTwentyOne);
+        registry.register(day_twenty_two::DayTwentyTwo);
+        registry.register(day_twenty_three::DayTwentyThree);
+        registry.register(christmas_eve::ChristmasEve);
+        registry.register(christmas_day::ChristmasDay);
+        registry
+    }
+}
+
+// The days of |year|, None if it doesn't have any.
+pub fn registry_for_year(year: u32) -> Option<Registry> {
+    match year {
+        DEFAULT_YEAR => Some(Registry::default()),
+        // The new-day command adds every other year below.
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_is_complete_and_ordered() {
+        let registry = Registry::default();
+        assert_eq!(25, registry.len());
+        for (i, solver) in registry.iter().enumerate() {
+            assert_eq!(i as u32 + 1, solver.day());
+        }
+    }
+
+    #[test]
+    fn test_parts_individually() {
+        let registry = Registry::default();
+        let day_eight = registry.get(8).unwrap();
+        assert_eq!("Handheld Halting", day_eight.title());
+
+        let input = day_eight.parse("input/day_eight.txt").unwrap();
+        assert_eq!(
+            Answer::Number(1814),
+            day_eight.part_one(input.as_ref()).unwrap()
+        );
+        assert_eq!(
+            Answer::Number(1056),
+            day_eight.part_two(input.as_ref()).unwrap()
+        );
+        assert!(registry.get(26).is_none());
+    }
+
+    #[test]
+    fn test_run_with_other_input() {
+        let registry = Registry::default();
+        let day_eight = registry.get(8).unwrap();
+        assert_eq!(
+            Solution {
+                day: 8,
+                part_one: Answer::Number(1814),
+                part_two: Answer::Number(1056),
+            },
+            run(day_eight, None).unwrap()
+        );
+        assert_eq!(
+            Answer::Number(5),
+            run(day_eight, Some("input/day_eight_example.txt"))
+                .unwrap()
+                .part_one
+        );
+    }
+
+    #[test]
+    fn test_run_part() {
+        let registry = Registry::default();
+        let day_two = registry.get(2).unwrap();
+        assert_eq!(
+            Answer::Number(443),
+            run_part(day_two, None, Part::Two).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_run_with_malformed_input() {
+        let registry = Registry::default();
+        let day_eight = registry.get(8).unwrap();
+        let err = run(day_eight, Some("input/day_one.txt")).unwrap_err();
+        assert!(err.to_string().starts_with("input/day_one.txt:1:"));
+    }
+
+    #[test]
+    fn test_day_name() {
+        assert_eq!("Day 3", day_name(3));
+        assert_eq!("Christmas Eve", day_name(24));
+        assert_eq!("Christmas Day", day_name(25));
+    }
+
+    #[test]
+    fn test_resolve_input() {
+        let registry = Registry::default();
+        let day_one = registry.get(1).unwrap();
+        assert_eq!("input/day_one.txt", resolve_input(day_one, None));
+        assert_eq!("other.txt", resolve_input(day_one, Some("other.txt")));
+        assert_eq!(STDIN_FILENAME, resolve_input(day_one, Some(STDIN_FILENAME)));
+
+        let christmas_day = registry.get(25).unwrap();
+        assert_eq!("", resolve_input(christmas_day, None));
+    }
+
+    #[test]
+    fn test_registry_for_year() {
+        assert_eq!(25, registry_for_year(DEFAULT_YEAR).unwrap().len());
+        assert!(registry_for_year(1999).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_registering_twice() {
+        let mut registry = Registry::default();
+        registry.register(day_eight::DayEight);
+    }
+}
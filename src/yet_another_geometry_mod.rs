@@ -1,13 +1,22 @@
+use crate::error::Error;
+use std::collections::HashMap;
 use std::convert::TryInto;
+use std::error;
 use std::fmt;
+use std::hash::Hash;
+use std::iter::FromIterator;
 use std::ops::Add;
 use std::ops::AddAssign;
+use std::ops::{Div, Index, IndexMut, Mul, Neg, Sub, SubAssign};
+use std::str::FromStr;
 
-pub trait Advance {
-    fn advance(self: &mut Self, direction: Direction);
-    fn advance_copy(self: &Self, direction: Direction) -> Self;
-    fn advance_mult(self: &mut Self, direction: Direction, count: i64);
-    fn advance_copy_mult(self: &Self, direction: Direction, count: i64) -> Self;
+// Points can advance in any kind of direction |D| that makes sense for
+// them, e.g. Point2D in a |Direction| or |Direction8|.
+pub trait Advance<D = Direction> {
+    fn advance(&mut self, direction: D);
+    fn advance_copy(&self, direction: D) -> Self;
+    fn advance_mult(&mut self, direction: D, count: i64);
+    fn advance_copy_mult(&self, direction: D, count: i64) -> Self;
 }
 
 pub trait ArrayLike {
@@ -25,11 +34,86 @@ pub trait Inversable {
     fn inverse(&self) -> Self;
 }
 
+// Rotations are only supported in multiples of 90 degrees.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct InvalidAngle(pub i64);
+
+impl fmt::Display for InvalidAngle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} degrees is not a multiple of 90", self.0)
+    }
+}
+
+impl error::Error for InvalidAngle {}
+
+// Converts |degrees| to the equivalent number of quarter turns to the left
+// (counterclockwise), from 0 to 3. Negative angles turn right.
+pub fn quarter_turns(degrees: i64) -> Result<i64, InvalidAngle> {
+    if degrees % 90 != 0 {
+        return Err(InvalidAngle(degrees));
+    }
+    Ok((degrees / 90).rem_euclid(4))
+}
+
+// Directions and matrices turn the way they look on a screen, while points
+// turn counterclockwise when y grows upwards. Use |CoordinateSystem| to turn a
+// point the way it looks.
 pub trait Rotatable {
-    // TODO: fancier version.
     fn rotate_90_deg_left(&mut self);
     fn rotate_90_deg_right(&mut self);
     fn rotate_180_deg(&mut self);
+
+    // Any multiple of 90 degrees, positive to the left and negative to the
+    // right, e.g. -90 and 270 are the same thing.
+    fn rotate_left(&mut self, degrees: i64) -> Result<(), InvalidAngle> {
+        match quarter_turns(degrees)? {
+            1 => self.rotate_90_deg_left(),
+            2 => self.rotate_180_deg(),
+            3 => self.rotate_90_deg_right(),
+            _ => (),
+        }
+        Ok(())
+    }
+
+    fn rotate_right(&mut self, degrees: i64) -> Result<(), InvalidAngle> {
+        self.rotate_left(-degrees)
+    }
+
+    // Same as |rotate_left|, but around |pivot| instead of the origin.
+    fn rotate_left_about(&mut self, pivot: Self, degrees: i64) -> Result<(), InvalidAngle>
+    where
+        Self: Copy + Add<Output = Self> + Sub<Output = Self>,
+    {
+        let mut offset = *self - pivot;
+        offset.rotate_left(degrees)?;
+        *self = pivot + offset;
+        Ok(())
+    }
+
+    fn rotate_right_about(&mut self, pivot: Self, degrees: i64) -> Result<(), InvalidAngle>
+    where
+        Self: Copy + Add<Output = Self> + Sub<Output = Self>,
+    {
+        self.rotate_left_about(pivot, -degrees)
+    }
+}
+
+// Lines through the origin to reflect across.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Axis {
+    // The line y = 0.
+    X,
+    // The line x = 0.
+    Y,
+    // The line y = x.
+    Diagonal,
+    // The line y = -x.
+    AntiDiagonal,
+}
+
+pub trait Reflectable {
+    fn reflect(&mut self, axis: Axis);
+    fn reflect_copy(&self, axis: Axis) -> Self;
 }
 
 pub trait Matrix2DLike<T> {
@@ -40,26 +124,78 @@ pub trait Matrix2DLike<T> {
     fn set(&mut self, location: &Point2D, value: T);
 }
 
+// Turns by the smallest step the direction type has, e.g. 90 degrees for a
+// |Direction| but 45 for a |Direction8|.
 pub trait RelativeTurn {
-    fn to_left(self: &Self) -> Direction;
-    fn to_right(self: &Self) -> Direction;
+    fn to_left(&self) -> Self;
+    fn to_right(&self) -> Self;
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+// Directions for grids that are looked at, so up is up on the screen. See
+// |CoordinateSystem| for what that means for y.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub enum Direction {
     Left,
+    #[default]
     Up,
     Right,
     Down,
 }
 
-#[derive(Debug, PartialEq, Eq, Default, Hash, Copy, Clone)]
+// Directions whose unit step is known. Like everything else here, the step
+// is in screen coordinates unless it goes through a |CoordinateSystem|.
+pub trait UnitOffset {
+    fn offset(&self) -> Point2D;
+}
+
+// Which way y grows. Everything in this module defaults to the screen
+// system, which is how grids read from input are laid out (the first line
+// is row zero). Maps and compasses usually have y growing upwards instead.
+// Either way, north is up and east is right.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CoordinateSystem {
+    // y grows downwards, so north is negative y.
+    #[default]
+    Screen,
+    // y grows upwards, so north is positive y.
+    Compass,
+}
+
+// The four directions plus the diagonals between them. Like |Direction|,
+// north is up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Direction8 {
+    North,
+    NorthEast,
+    East,
+    SouthEast,
+    South,
+    SouthWest,
+    West,
+    NorthWest,
+}
+
+// Directions on a grid of hexagons with pointy tops, so that neighbouring
+// hexagons are to the east and west but not north and south.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HexDirection {
+    East,
+    SouthEast,
+    SouthWest,
+    West,
+    NorthWest,
+    NorthEast,
+}
+
+// Ordered by x, then by y.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Default, Hash, Copy, Clone)]
 pub struct Point2D {
     pub x: i64,
     pub y: i64,
 }
 
-#[derive(Debug, PartialEq, Eq, Default, Hash, Copy, Clone)]
+// Ordered by x, then by y, then by z.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Default, Hash, Copy, Clone)]
 pub struct Point3D {
     pub x: i64,
     pub y: i64,
@@ -74,18 +210,19 @@ pub struct Point4D {
     pub w: i64,
 }
 
+// A point with any number of dimensions, for code that works the same way
+// no matter how many there are.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
+pub struct Point<const N: usize> {
+    pub coordinates: [i64; N],
+}
+
 #[derive(Debug, PartialEq, Eq, Default, Hash, Clone)]
 pub struct Matrix2D<T> {
     pub data: Vec<T>,
     pub size: Point2D,
 }
 
-impl Default for Direction {
-    fn default() -> Self {
-        return Direction::Up;
-    }
-}
-
 impl Inversable for Direction {
     fn inverse(&self) -> Self {
         match self {
@@ -98,7 +235,7 @@ impl Inversable for Direction {
 }
 
 impl RelativeTurn for Direction {
-    fn to_left(self: &Self) -> Direction {
+    fn to_left(&self) -> Direction {
         match self {
             Direction::Left => Direction::Down,
             Direction::Down => Direction::Right,
@@ -107,7 +244,7 @@ impl RelativeTurn for Direction {
         }
     }
 
-    fn to_right(self: &Self) -> Direction {
+    fn to_right(&self) -> Direction {
         match self {
             Direction::Up => Direction::Right,
             Direction::Right => Direction::Down,
@@ -117,16 +254,296 @@ impl RelativeTurn for Direction {
     }
 }
 
+impl Direction {
+    // Compass names, which are the same in every |CoordinateSystem|.
+    pub const NORTH: Direction = Direction::Up;
+    pub const EAST: Direction = Direction::Right;
+    pub const SOUTH: Direction = Direction::Down;
+    pub const WEST: Direction = Direction::Left;
+
+    // Clockwise, starting from north.
+    pub const ALL: [Direction; 4] = [
+        Direction::NORTH,
+        Direction::EAST,
+        Direction::SOUTH,
+        Direction::WEST,
+    ];
+
+    pub fn compass_name(&self) -> &'static str {
+        match self {
+            Direction::Up => "north",
+            Direction::Right => "east",
+            Direction::Down => "south",
+            Direction::Left => "west",
+        }
+    }
+}
+
+impl UnitOffset for Direction {
+    fn offset(&self) -> Point2D {
+        Point2D::default().advance_copy(*self)
+    }
+}
+
+impl CoordinateSystem {
+    // The unit step for |direction| in this system.
+    pub fn offset<D: UnitOffset>(&self, direction: D) -> Point2D {
+        let offset = direction.offset();
+        match self {
+            CoordinateSystem::Screen => offset,
+            CoordinateSystem::Compass => Point2D {
+                x: offset.x,
+                y: -offset.y,
+            },
+        }
+    }
+
+    pub fn advance<D: UnitOffset>(&self, point: Point2D, direction: D, count: i64) -> Point2D {
+        point + self.offset(direction) * count
+    }
+
+    // |Rotatable| turns points counterclockwise when y grows upwards, which
+    // looks clockwise on a screen. These turn |point| the way it looks in this
+    // system. Directions mean the same in every system, so they turn on their
+    // own.
+    pub fn rotate_left(&self, point: &mut Point2D, degrees: i64) -> Result<(), InvalidAngle> {
+        match self {
+            CoordinateSystem::Screen => point.rotate_right(degrees),
+            CoordinateSystem::Compass => point.rotate_left(degrees),
+        }
+    }
+
+    pub fn rotate_right(&self, point: &mut Point2D, degrees: i64) -> Result<(), InvalidAngle> {
+        self.rotate_left(point, -degrees)
+    }
+}
+
+impl Rotatable for Direction {
+    fn rotate_90_deg_left(&mut self) {
+        *self = self.to_left();
+    }
+
+    fn rotate_90_deg_right(&mut self) {
+        *self = self.to_right();
+    }
+
+    fn rotate_180_deg(&mut self) {
+        *self = self.inverse();
+    }
+}
+
+impl Direction8 {
+    // Clockwise, starting from north.
+    pub const ALL: [Direction8; 8] = [
+        Direction8::North,
+        Direction8::NorthEast,
+        Direction8::East,
+        Direction8::SouthEast,
+        Direction8::South,
+        Direction8::SouthWest,
+        Direction8::West,
+        Direction8::NorthWest,
+    ];
+
+    pub fn iter() -> impl Iterator<Item = Direction8> {
+        Direction8::ALL.iter().copied()
+    }
+
+    fn index(&self) -> usize {
+        Direction8::ALL.iter().position(|d| d == self).unwrap()
+    }
+
+    // Clockwise, so a negative |steps| turns counterclockwise.
+    fn turned(&self, steps: i64) -> Direction8 {
+        Direction8::ALL[(self.index() as i64 + steps).rem_euclid(8) as usize]
+    }
+}
+
+impl UnitOffset for Direction8 {
+    fn offset(&self) -> Point2D {
+        match self {
+            Direction8::North => Point2D { x: 0, y: -1 },
+            Direction8::NorthEast => Point2D { x: 1, y: -1 },
+            Direction8::East => Point2D { x: 1, y: 0 },
+            Direction8::SouthEast => Point2D { x: 1, y: 1 },
+            Direction8::South => Point2D { x: 0, y: 1 },
+            Direction8::SouthWest => Point2D { x: -1, y: 1 },
+            Direction8::West => Point2D { x: -1, y: 0 },
+            Direction8::NorthWest => Point2D { x: -1, y: -1 },
+        }
+    }
+}
+
+impl RelativeTurn for Direction8 {
+    fn to_left(&self) -> Self {
+        self.turned(-1)
+    }
+
+    fn to_right(&self) -> Self {
+        self.turned(1)
+    }
+}
+
+impl Inversable for Direction8 {
+    fn inverse(&self) -> Self {
+        self.turned(4)
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(direction: Direction) -> Self {
+        match direction {
+            Direction::Left => Direction8::West,
+            Direction::Up => Direction8::North,
+            Direction::Right => Direction8::East,
+            Direction::Down => Direction8::South,
+        }
+    }
+}
+
+// Lower case compass abbreviations, e.g. "n" or "sw".
+impl FromStr for Direction8 {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "n" => Ok(Direction8::North),
+            "ne" => Ok(Direction8::NorthEast),
+            "e" => Ok(Direction8::East),
+            "se" => Ok(Direction8::SouthEast),
+            "s" => Ok(Direction8::South),
+            "sw" => Ok(Direction8::SouthWest),
+            "w" => Ok(Direction8::West),
+            "nw" => Ok(Direction8::NorthWest),
+            _ => Err(Error::parse(format!("unknown direction '{}'", s))),
+        }
+    }
+}
+
+impl HexDirection {
+    // Clockwise, starting from east.
+    pub const ALL: [HexDirection; 6] = [
+        HexDirection::East,
+        HexDirection::SouthEast,
+        HexDirection::SouthWest,
+        HexDirection::West,
+        HexDirection::NorthWest,
+        HexDirection::NorthEast,
+    ];
+
+    pub fn iter() -> impl Iterator<Item = HexDirection> {
+        HexDirection::ALL.iter().copied()
+    }
+
+    fn index(&self) -> usize {
+        HexDirection::ALL.iter().position(|d| d == self).unwrap()
+    }
+
+    // Clockwise, so a negative |steps| turns counterclockwise.
+    fn turned(&self, steps: i64) -> HexDirection {
+        HexDirection::ALL[(self.index() as i64 + steps).rem_euclid(6) as usize]
+    }
+
+    // The step to take in this direction in cube coordinates, where every
+    // hexagon has x + y + z = 0. See:
+    // https://www.redblobgames.com/grids/hexagons/#coordinates
+    pub fn offset(&self) -> Point3D {
+        match self {
+            HexDirection::East => Point3D { x: 1, y: -1, z: 0 },
+            HexDirection::SouthEast => Point3D { x: 0, y: -1, z: 1 },
+            HexDirection::SouthWest => Point3D { x: -1, y: 0, z: 1 },
+            HexDirection::West => Point3D { x: -1, y: 1, z: 0 },
+            HexDirection::NorthWest => Point3D { x: 0, y: 1, z: -1 },
+            HexDirection::NorthEast => Point3D { x: 1, y: 0, z: -1 },
+        }
+    }
+}
+
+impl RelativeTurn for HexDirection {
+    fn to_left(&self) -> Self {
+        self.turned(-1)
+    }
+
+    fn to_right(&self) -> Self {
+        self.turned(1)
+    }
+}
+
+impl Inversable for HexDirection {
+    fn inverse(&self) -> Self {
+        self.turned(3)
+    }
+}
+
+// Lower case compass abbreviations, e.g. "e" or "nw".
+impl FromStr for HexDirection {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "e" => Ok(HexDirection::East),
+            "se" => Ok(HexDirection::SouthEast),
+            "sw" => Ok(HexDirection::SouthWest),
+            "w" => Ok(HexDirection::West),
+            "nw" => Ok(HexDirection::NorthWest),
+            "ne" => Ok(HexDirection::NorthEast),
+            _ => Err(Error::parse(format!("unknown hex direction '{}'", s))),
+        }
+    }
+}
+
+impl Advance<Direction8> for Point2D {
+    fn advance(&mut self, direction: Direction8) {
+        *self += direction.offset();
+    }
+
+    fn advance_copy(&self, direction: Direction8) -> Point2D {
+        *self + direction.offset()
+    }
+
+    fn advance_mult(&mut self, direction: Direction8, count: i64) {
+        *self += direction.offset().scale_copy(count);
+    }
+
+    fn advance_copy_mult(&self, direction: Direction8, count: i64) -> Point2D {
+        *self + direction.offset().scale_copy(count)
+    }
+}
+
+impl Advance<HexDirection> for Point3D {
+    fn advance(&mut self, direction: HexDirection) {
+        *self += direction.offset();
+    }
+
+    fn advance_copy(&self, direction: HexDirection) -> Point3D {
+        *self + direction.offset()
+    }
+
+    fn advance_mult(&mut self, direction: HexDirection, count: i64) {
+        *self = self.advance_copy_mult(direction, count);
+    }
+
+    fn advance_copy_mult(&self, direction: HexDirection, count: i64) -> Point3D {
+        let offset = direction.offset();
+        *self
+            + Point3D {
+                x: offset.x * count,
+                y: offset.y * count,
+                z: offset.z * count,
+            }
+    }
+}
+
 impl Advance for Point2D {
-    fn advance(self: &mut Self, direction: Direction) {
+    fn advance(&mut self, direction: Direction) {
         self.advance_mult(direction, 1);
     }
 
-    fn advance_copy(self: &Self, direction: Direction) -> Point2D {
+    fn advance_copy(&self, direction: Direction) -> Point2D {
         self.advance_copy_mult(direction, 1)
     }
 
-    fn advance_mult(self: &mut Self, direction: Direction, count: i64) {
+    fn advance_mult(&mut self, direction: Direction, count: i64) {
         match direction {
             Direction::Left => self.x -= count,
             Direction::Up => self.y -= count,
@@ -135,7 +552,7 @@ impl Advance for Point2D {
         }
     }
 
-    fn advance_copy_mult(self: &Self, direction: Direction, count: i64) -> Point2D {
+    fn advance_copy_mult(&self, direction: Direction, count: i64) -> Point2D {
         match direction {
             Direction::Left => Point2D {
                 x: self.x - count,
@@ -202,6 +619,101 @@ impl AddAssign for Point2D {
     }
 }
 
+impl Sub for Point2D {
+    type Output = Self;
+    fn sub(self, other: Self) -> Self {
+        Self {
+            x: self.x - other.x,
+            y: self.y - other.y,
+        }
+    }
+}
+
+impl SubAssign for Point2D {
+    fn sub_assign(&mut self, other: Self) {
+        self.x -= other.x;
+        self.y -= other.y;
+    }
+}
+
+impl Neg for Point2D {
+    type Output = Self;
+    fn neg(self) -> Self {
+        Self {
+            x: -self.x,
+            y: -self.y,
+        }
+    }
+}
+
+impl Mul<i64> for Point2D {
+    type Output = Self;
+    fn mul(self, factor: i64) -> Self {
+        self.scale_copy(factor)
+    }
+}
+
+// Rounds towards zero, like integer division does.
+impl Div<i64> for Point2D {
+    type Output = Self;
+    fn div(self, divisor: i64) -> Self {
+        Self {
+            x: self.x / divisor,
+            y: self.y / divisor,
+        }
+    }
+}
+
+impl Point2D {
+    pub fn manhattan_distance(&self, other: &Point2D) -> i64 {
+        (self.x - other.x).abs() + (self.y - other.y).abs()
+    }
+
+    // The number of king's moves between the points.
+    pub fn chebyshev_distance(&self, other: &Point2D) -> i64 {
+        (self.x - other.x).abs().max((self.y - other.y).abs())
+    }
+
+    // Squared, so that it stays an exact integer.
+    pub fn squared_euclidean_distance(&self, other: &Point2D) -> i64 {
+        let difference = *self - *other;
+        difference.dot(&difference)
+    }
+
+    pub fn dot(&self, other: &Point2D) -> i64 {
+        self.x * other.x + self.y * other.y
+    }
+
+    // The z component of the 3D cross product: positive when |other| is
+    // counterclockwise from |self| in a y-up system.
+    pub fn cross(&self, other: &Point2D) -> i64 {
+        self.x * other.y - self.y * other.x
+    }
+
+    // Each component clamped to -1, 0 or 1, e.g. the single step to take
+    // towards a point in a straight or diagonal line.
+    pub fn signum(&self) -> Point2D {
+        Point2D {
+            x: self.x.signum(),
+            y: self.y.signum(),
+        }
+    }
+
+    pub fn component_min(&self, other: &Point2D) -> Point2D {
+        Point2D {
+            x: self.x.min(other.x),
+            y: self.y.min(other.y),
+        }
+    }
+
+    pub fn component_max(&self, other: &Point2D) -> Point2D {
+        Point2D {
+            x: self.x.max(other.x),
+            y: self.y.max(other.y),
+        }
+    }
+}
+
 impl Scalable for Point2D {
     fn scale(&mut self, factor: i64) {
         self.x *= factor;
@@ -227,7 +739,7 @@ impl Rotatable for Point2D {
     fn rotate_90_deg_right(&mut self) {
         // Example: (-10, 4) rotated right/cw = (4, 10)
         let tmp = self.y;
-        self.y = -1 * self.x;
+        self.y = -self.x;
         self.x = tmp;
     }
 
@@ -238,6 +750,22 @@ impl Rotatable for Point2D {
     }
 }
 
+impl Reflectable for Point2D {
+    fn reflect(&mut self, axis: Axis) {
+        *self = self.reflect_copy(axis);
+    }
+
+    fn reflect_copy(&self, axis: Axis) -> Self {
+        let Point2D { x, y } = *self;
+        match axis {
+            Axis::X => Point2D { x, y: -y },
+            Axis::Y => Point2D { x: -x, y },
+            Axis::Diagonal => Point2D { x: y, y: x },
+            Axis::AntiDiagonal => Point2D { x: -y, y: -x },
+        }
+    }
+}
+
 impl fmt::Display for Point3D {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "({}, {}, {})", self.x, self.y, self.z)
@@ -308,60 +836,1064 @@ impl AddAssign for Point4D {
     }
 }
 
-impl<T: Copy> Matrix2DLike<T> for Matrix2D<T> {
-    fn create(size: &Point2D) -> Self {
-        Matrix2D {
-            data: Vec::with_capacity((size.x * size.y + 1).try_into().unwrap()),
-            size: size.clone(),
+impl fmt::Display for Point4D {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {}, {})", self.x, self.y, self.z, self.w)
+    }
+}
+
+impl ArrayLike for Point4D {
+    fn size() -> usize {
+        4
+    }
+
+    fn get(&self, i: usize) -> i64 {
+        match i {
+            0 => self.x,
+            1 => self.y,
+            2 => self.z,
+            3 => self.w,
+            _ => panic!("out of bounds"),
         }
     }
 
-    fn create_with_data(size: &Point2D, data: Vec<T>) -> Self {
-        Matrix2D {
-            data: data,
-            size: size.clone(),
+    fn set(&mut self, i: usize, v: i64) {
+        match i {
+            0 => self.x = v,
+            1 => self.y = v,
+            2 => self.z = v,
+            3 => self.w = v,
+            _ => panic!("out of bounds"),
         }
     }
+}
 
-    fn size(&self) -> &Point2D {
-        &self.size
+impl<const N: usize> Point<N> {
+    pub fn new(coordinates: [i64; N]) -> Self {
+        Point { coordinates }
     }
 
-    fn get(&self, location: &Point2D) -> T {
-        assert!(location.x < self.size.x);
-        assert!(location.y < self.size.y);
-        self.data[(location.y * self.size.x + location.x) as usize]
+    pub fn manhattan_distance(&self, other: &Self) -> i64 {
+        (*self - *other).coordinates.iter().map(|c| c.abs()).sum()
     }
 
-    fn set(&mut self, location: &Point2D, value: T) {
-        assert!(location.x < self.size.x);
-        assert!(location.y < self.size.y);
+    // The number of king's moves between the points, in any dimension.
+    pub fn chebyshev_distance(&self, other: &Self) -> i64 {
+        (*self - *other)
+            .coordinates
+            .iter()
+            .map(|c| c.abs())
+            .max()
+            .unwrap_or_default()
+    }
+
+    // Offsets to every point touching this one, including diagonally: all
+    // 3^N - 1 of them.
+    pub fn neighbor_offsets() -> Vec<Self> {
+        let mut offsets = vec![Self::default()];
+        for i in 0..N {
+            offsets = offsets
+                .into_iter()
+                .flat_map(|offset| {
+                    (-1..=1).map(move |delta| {
+                        let mut offset = offset;
+                        offset.coordinates[i] = delta;
+                        offset
+                    })
+                })
+                .collect();
+        }
+        offsets.retain(|offset| *offset != Self::default());
+        offsets
+    }
 
-        self.data[(location.y * self.size.x + location.x) as usize] = value;
+    // Offsets to the 2N points sharing a face with this one.
+    pub fn orthogonal_offsets() -> Vec<Self> {
+        (0..N)
+            .flat_map(|i| {
+                [-1, 1].iter().map(move |delta| {
+                    let mut offset = Self::default();
+                    offset.coordinates[i] = *delta;
+                    offset
+                })
+            })
+            .collect()
     }
 }
 
-impl<T: fmt::Display + Copy> fmt::Display for Matrix2D<T> {
+// Arrays only implement Default up to a fixed size.
+impl<const N: usize> Default for Point<N> {
+    fn default() -> Self {
+        Point {
+            coordinates: [0; N],
+        }
+    }
+}
+
+impl<const N: usize> fmt::Display for Point<N> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut current = Point2D::default();
-        for i in 0..self.size.y {
-            current.y = i;
-            write!(f, "[").expect("didn't write!");
-            for j in 0..self.size.x {
-                current.x = j;
-                write!(f, "{} ", self.get(&current)).expect("didn't write!");
+        write!(f, "(")?;
+        for (i, c) in self.coordinates.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
             }
-            write!(f, "]\n").expect("didn't write!");
+            write!(f, "{}", c)?;
         }
-        Ok(())
+        write!(f, ")")
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl<const N: usize> ArrayLike for Point<N> {
+    fn size() -> usize {
+        N
+    }
 
-    #[test]
+    fn get(&self, i: usize) -> i64 {
+        self.coordinates[i]
+    }
+
+    fn set(&mut self, i: usize, v: i64) {
+        self.coordinates[i] = v;
+    }
+}
+
+impl<const N: usize> Index<usize> for Point<N> {
+    type Output = i64;
+    fn index(&self, i: usize) -> &i64 {
+        &self.coordinates[i]
+    }
+}
+
+impl<const N: usize> IndexMut<usize> for Point<N> {
+    fn index_mut(&mut self, i: usize) -> &mut i64 {
+        &mut self.coordinates[i]
+    }
+}
+
+impl<const N: usize> Add for Point<N> {
+    type Output = Self;
+    fn add(mut self, other: Self) -> Self {
+        self += other;
+        self
+    }
+}
+
+impl<const N: usize> AddAssign for Point<N> {
+    fn add_assign(&mut self, other: Self) {
+        for (c, o) in self.coordinates.iter_mut().zip(other.coordinates.iter()) {
+            *c += o;
+        }
+    }
+}
+
+impl<const N: usize> Sub for Point<N> {
+    type Output = Self;
+    fn sub(mut self, other: Self) -> Self {
+        self -= other;
+        self
+    }
+}
+
+impl<const N: usize> SubAssign for Point<N> {
+    fn sub_assign(&mut self, other: Self) {
+        for (c, o) in self.coordinates.iter_mut().zip(other.coordinates.iter()) {
+            *c -= o;
+        }
+    }
+}
+
+impl<const N: usize> Neg for Point<N> {
+    type Output = Self;
+    fn neg(mut self) -> Self {
+        self.coordinates.iter_mut().for_each(|c| *c = -*c);
+        self
+    }
+}
+
+impl<const N: usize> Mul<i64> for Point<N> {
+    type Output = Self;
+    fn mul(mut self, factor: i64) -> Self {
+        self.coordinates.iter_mut().for_each(|c| *c *= factor);
+        self
+    }
+}
+
+impl<const N: usize> Scalable for Point<N> {
+    fn scale(&mut self, factor: i64) {
+        *self = *self * factor;
+    }
+
+    fn scale_copy(&self, factor: i64) -> Self {
+        *self * factor
+    }
+}
+
+impl From<Point2D> for Point<2> {
+    fn from(p: Point2D) -> Self {
+        Point::new([p.x, p.y])
+    }
+}
+
+impl From<Point<2>> for Point2D {
+    fn from(p: Point<2>) -> Self {
+        let [x, y] = p.coordinates;
+        Point2D { x, y }
+    }
+}
+
+impl From<Point3D> for Point<3> {
+    fn from(p: Point3D) -> Self {
+        Point::new([p.x, p.y, p.z])
+    }
+}
+
+impl From<Point<3>> for Point3D {
+    fn from(p: Point<3>) -> Self {
+        let [x, y, z] = p.coordinates;
+        Point3D { x, y, z }
+    }
+}
+
+impl From<Point4D> for Point<4> {
+    fn from(p: Point4D) -> Self {
+        Point::new([p.x, p.y, p.z, p.w])
+    }
+}
+
+impl From<Point<4>> for Point4D {
+    fn from(p: Point<4>) -> Self {
+        let [x, y, z, w] = p.coordinates;
+        Point4D { x, y, z, w }
+    }
+}
+
+impl<T: Copy> Matrix2DLike<T> for Matrix2D<T> {
+    fn create(size: &Point2D) -> Self {
+        Matrix2D {
+            data: Vec::with_capacity((size.x * size.y + 1).try_into().unwrap()),
+            size: *size,
+        }
+    }
+
+    fn create_with_data(size: &Point2D, data: Vec<T>) -> Self {
+        Matrix2D { data, size: *size }
+    }
+
+    fn size(&self) -> &Point2D {
+        &self.size
+    }
+
+    fn get(&self, location: &Point2D) -> T {
+        self[*location]
+    }
+
+    fn set(&mut self, location: &Point2D, value: T) {
+        self[*location] = value;
+    }
+}
+
+// Which axes wrap around, so that walking off one edge of a matrix comes
+// back in from the opposite one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Wrap {
+    pub x: bool,
+    pub y: bool,
+}
+
+impl Wrap {
+    pub const NONE: Wrap = Wrap { x: false, y: false };
+    pub const HORIZONTAL: Wrap = Wrap { x: true, y: false };
+    pub const VERTICAL: Wrap = Wrap { x: false, y: true };
+    // Also known as a torus.
+    pub const BOTH: Wrap = Wrap { x: true, y: true };
+}
+
+impl<T> Matrix2D<T> {
+    pub fn contains(&self, location: &Point2D) -> bool {
+        (0..self.size.x).contains(&location.x) && (0..self.size.y).contains(&location.y)
+    }
+
+    fn index_of(&self, location: &Point2D) -> Option<usize> {
+        if self.contains(location) {
+            Some((location.y * self.size.x + location.x) as usize)
+        } else {
+            None
+        }
+    }
+
+    pub fn try_get(&self, location: &Point2D) -> Option<&T> {
+        self.index_of(location).map(|i| &self.data[i])
+    }
+
+    pub fn get_mut(&mut self, location: &Point2D) -> Option<&mut T> {
+        self.index_of(location).map(move |i| &mut self.data[i])
+    }
+
+    // Moves |location| back inside the matrix along the axes that |wrap|,
+    // or returns None if it's outside along an axis that doesn't.
+    pub fn wrap(&self, location: &Point2D, wrap: Wrap) -> Option<Point2D> {
+        let wrapped = Point2D {
+            x: if wrap.x && self.size.x > 0 {
+                location.x.rem_euclid(self.size.x)
+            } else {
+                location.x
+            },
+            y: if wrap.y && self.size.y > 0 {
+                location.y.rem_euclid(self.size.y)
+            } else {
+                location.y
+            },
+        };
+        if self.contains(&wrapped) {
+            Some(wrapped)
+        } else {
+            None
+        }
+    }
+
+    pub fn get_wrapping(&self, location: &Point2D, wrap: Wrap) -> Option<&T> {
+        self.wrap(location, wrap).and_then(|l| self.try_get(&l))
+    }
+
+    pub fn get_wrapping_mut(&mut self, location: &Point2D, wrap: Wrap) -> Option<&mut T> {
+        match self.wrap(location, wrap) {
+            Some(l) => self.get_mut(&l),
+            None => None,
+        }
+    }
+
+    fn out_of_bounds(&self, location: &Point2D) -> ! {
+        panic!(
+            "{} is outside of the {}x{} matrix",
+            location, self.size.x, self.size.y
+        )
+    }
+}
+
+// Panics if |location| is outside of the matrix, see |Matrix2D::try_get| for
+// a version that doesn't.
+impl<T> Index<Point2D> for Matrix2D<T> {
+    type Output = T;
+    fn index(&self, location: Point2D) -> &T {
+        match self.index_of(&location) {
+            Some(i) => &self.data[i],
+            None => self.out_of_bounds(&location),
+        }
+    }
+}
+
+impl<T> IndexMut<Point2D> for Matrix2D<T> {
+    fn index_mut(&mut self, location: Point2D) -> &mut T {
+        match self.index_of(&location) {
+            Some(i) => &mut self.data[i],
+            None => self.out_of_bounds(&location),
+        }
+    }
+}
+
+impl<T> Matrix2D<T> {
+    // Builds a matrix of |size| by calling |f| for every location, row by
+    // row.
+    pub fn from_fn<F>(size: Point2D, mut f: F) -> Matrix2D<T>
+    where
+        F: FnMut(Point2D) -> T,
+    {
+        let mut data = Vec::with_capacity((size.x * size.y).max(0) as usize);
+        for y in 0..size.y {
+            for x in 0..size.x {
+                data.push(f(Point2D { x, y }));
+            }
+        }
+        Matrix2D { data, size }
+    }
+
+    // The |size| rectangle with its top left corner at |origin|, or None if
+    // any of it is outside of the matrix.
+    pub fn window(&self, origin: Point2D, size: Point2D) -> Option<MatrixView<'_, T>> {
+        let end = origin + size;
+        if origin.x < 0 || origin.y < 0 || size.x < 0 || size.y < 0 {
+            return None;
+        }
+        if end.x > self.size.x || end.y > self.size.y {
+            return None;
+        }
+        Some(MatrixView {
+            matrix: self,
+            origin,
+            size,
+        })
+    }
+
+    pub fn row(&self, y: i64) -> Option<MatrixView<'_, T>> {
+        self.window(
+            Point2D { x: 0, y },
+            Point2D {
+                x: self.size.x,
+                y: 1,
+            },
+        )
+    }
+
+    pub fn column(&self, x: i64) -> Option<MatrixView<'_, T>> {
+        self.window(
+            Point2D { x, y: 0 },
+            Point2D {
+                x: 1,
+                y: self.size.y,
+            },
+        )
+    }
+
+    // The outermost row or column on |side|, read left to right or top to
+    // bottom. None if the matrix is empty.
+    pub fn border(&self, side: Direction) -> Option<MatrixView<'_, T>> {
+        match side {
+            Direction::Up => self.row(0),
+            Direction::Down => self.row(self.size.y - 1),
+            Direction::Left => self.column(0),
+            Direction::Right => self.column(self.size.x - 1),
+        }
+    }
+
+    // Every location in the matrix, row by row.
+    pub fn iter_points(&self) -> impl Iterator<Item = Point2D> {
+        let size = self.size;
+        (0..size.y).flat_map(move |y| (0..size.x).map(move |x| Point2D { x, y }))
+    }
+
+    // Every cell along with its location, row by row.
+    pub fn enumerate(&self) -> impl Iterator<Item = (Point2D, &T)> {
+        self.iter_points().zip(self.data.iter())
+    }
+
+    pub fn rows(&self) -> impl Iterator<Item = MatrixView<'_, T>> {
+        (0..self.size.y).map(move |y| self.row(y).unwrap())
+    }
+
+    pub fn columns(&self) -> impl Iterator<Item = MatrixView<'_, T>> {
+        (0..self.size.x).map(move |x| self.column(x).unwrap())
+    }
+
+    // The cells up, right, down and left of |location| that are inside the
+    // matrix.
+    pub fn neighbors4(&self, location: &Point2D) -> impl Iterator<Item = (Point2D, &T)> {
+        self.neighbors(location, Direction::ALL.iter().copied())
+    }
+
+    // Same as |neighbors4|, but diagonals too, clockwise from north.
+    pub fn neighbors8(&self, location: &Point2D) -> impl Iterator<Item = (Point2D, &T)> {
+        self.neighbors(location, Direction8::iter())
+    }
+
+    fn neighbors<D, I>(
+        &self,
+        location: &Point2D,
+        directions: I,
+    ) -> impl Iterator<Item = (Point2D, &T)>
+    where
+        D: UnitOffset,
+        I: Iterator<Item = D>,
+    {
+        let location = *location;
+        directions.filter_map(move |d| {
+            let neighbor = location + d.offset();
+            self.try_get(&neighbor).map(|value| (neighbor, value))
+        })
+    }
+
+    // The cells seen when looking from |location| towards |direction|, not
+    // including |location| itself, up to the edge of the matrix.
+    pub fn ray<D: UnitOffset>(
+        &self,
+        location: &Point2D,
+        direction: D,
+    ) -> impl Iterator<Item = (Point2D, &T)> {
+        let step = direction.offset();
+        let mut current = *location;
+        std::iter::from_fn(move || {
+            current += step;
+            self.try_get(&current).map(|value| (current, value))
+        })
+    }
+
+    // Same as |ray|, but stops after the first cell for which |stop| is
+    // true, e.g. the first thing that blocks the view.
+    pub fn ray_until<D, P>(
+        &self,
+        location: &Point2D,
+        direction: D,
+        mut stop: P,
+    ) -> impl Iterator<Item = (Point2D, &T)>
+    where
+        D: UnitOffset,
+        P: FnMut(&T) -> bool,
+    {
+        let mut stopped = false;
+        self.ray(location, direction).take_while(move |(_, value)| {
+            let keep_going = !stopped;
+            stopped = stop(value);
+            keep_going
+        })
+    }
+}
+
+impl<T: Clone> Matrix2D<T> {
+    // Swaps rows and columns, mirroring the matrix across its main diagonal.
+    pub fn transpose_copy(&self) -> Self {
+        Matrix2D::from_fn(
+            Point2D {
+                x: self.size.y,
+                y: self.size.x,
+            },
+            |p| self[Point2D { x: p.y, y: p.x }].clone(),
+        )
+    }
+
+    pub fn transpose(&mut self) {
+        *self = self.transpose_copy();
+    }
+
+    // Mirrors left and right.
+    pub fn flip_horizontal_copy(&self) -> Self {
+        Matrix2D::from_fn(self.size, |p| {
+            self[Point2D {
+                x: self.size.x - 1 - p.x,
+                y: p.y,
+            }]
+            .clone()
+        })
+    }
+
+    pub fn flip_horizontal(&mut self) {
+        *self = self.flip_horizontal_copy();
+    }
+
+    // Mirrors top and bottom.
+    pub fn flip_vertical_copy(&self) -> Self {
+        Matrix2D::from_fn(self.size, |p| {
+            self[Point2D {
+                x: p.x,
+                y: self.size.y - 1 - p.y,
+            }]
+            .clone()
+        })
+    }
+
+    pub fn flip_vertical(&mut self) {
+        *self = self.flip_vertical_copy();
+    }
+
+    // All eight ways the matrix can be rotated and flipped, starting with
+    // the matrix as it is, then its three rotations to the right, then the
+    // same for its horizontal mirror image.
+    pub fn orientations(&self) -> Vec<Self> {
+        let mut orientations = Vec::with_capacity(8);
+        for mut current in [self.clone(), self.flip_horizontal_copy()] {
+            for _ in 0..4 {
+                let next = {
+                    let mut next = current.clone();
+                    next.rotate_90_deg_right();
+                    next
+                };
+                orientations.push(current);
+                current = next;
+            }
+        }
+        orientations
+    }
+
+    // Joins a grid of matrices into one. Every matrix in a row of |grid|
+    // needs the same height, and every row the same total width.
+    pub fn stitch(grid: &[Vec<Matrix2D<T>>]) -> Self {
+        let mut data = Vec::new();
+        let mut size = Point2D::default();
+        for row in grid {
+            let height = row.first().map_or(0, |m| m.size.y);
+            assert!(
+                row.iter().all(|m| m.size.y == height),
+                "matrices in a row need the same height"
+            );
+            let width = row.iter().map(|m| m.size.x).sum();
+            assert!(
+                size.y == 0 || width == size.x,
+                "rows need the same width, expected {} but found {}",
+                size.x,
+                width
+            );
+            for y in 0..height {
+                for m in row {
+                    data.extend(m.row(y).unwrap().iter().cloned());
+                }
+            }
+            size.x = width;
+            size.y += height;
+        }
+        Matrix2D { data, size }
+    }
+}
+
+impl<T: Clone> Rotatable for Matrix2D<T> {
+    fn rotate_90_deg_left(&mut self) {
+        self.transpose();
+        self.flip_vertical();
+    }
+
+    fn rotate_90_deg_right(&mut self) {
+        self.transpose();
+        self.flip_horizontal();
+    }
+
+    fn rotate_180_deg(&mut self) {
+        self.flip_horizontal();
+        self.flip_vertical();
+    }
+}
+
+// Across the lines through the middle of the matrix, so that Axis::X (the
+// horizontal line) swaps top and bottom.
+impl<T: Clone> Reflectable for Matrix2D<T> {
+    fn reflect(&mut self, axis: Axis) {
+        *self = self.reflect_copy(axis);
+    }
+
+    fn reflect_copy(&self, axis: Axis) -> Self {
+        match axis {
+            Axis::X => self.flip_vertical_copy(),
+            Axis::Y => self.flip_horizontal_copy(),
+            Axis::Diagonal => self.transpose_copy(),
+            Axis::AntiDiagonal => {
+                let mut reflected = self.transpose_copy();
+                reflected.rotate_180_deg();
+                reflected
+            }
+        }
+    }
+}
+
+// A borrowed rectangle of a |Matrix2D|, with its own coordinates starting at
+// (0, 0).
+#[derive(Debug)]
+pub struct MatrixView<'a, T> {
+    matrix: &'a Matrix2D<T>,
+    origin: Point2D,
+    pub size: Point2D,
+}
+
+// Deriving these would require T to be Clone and Copy too.
+impl<'a, T> Clone for MatrixView<'a, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<'a, T> Copy for MatrixView<'a, T> {}
+
+impl<'a, T> MatrixView<'a, T> {
+    pub fn try_get(&self, location: &Point2D) -> Option<&'a T> {
+        if (0..self.size.x).contains(&location.x) && (0..self.size.y).contains(&location.y) {
+            self.matrix.try_get(&(self.origin + *location))
+        } else {
+            None
+        }
+    }
+
+    // Row by row.
+    pub fn iter(&self) -> impl Iterator<Item = &'a T> {
+        let view = *self;
+        (0..view.size.y).flat_map(move |y| {
+            (0..view.size.x).map(move |x| &view.matrix[view.origin + Point2D { x, y }])
+        })
+    }
+
+    pub fn to_matrix(&self) -> Matrix2D<T>
+    where
+        T: Clone,
+    {
+        Matrix2D {
+            data: self.iter().cloned().collect(),
+            size: self.size,
+        }
+    }
+}
+
+impl<'a, T> Index<Point2D> for MatrixView<'a, T> {
+    type Output = T;
+    fn index(&self, location: Point2D) -> &T {
+        self.try_get(&location).unwrap_or_else(|| {
+            panic!(
+                "{} is outside of the {}x{} view",
+                location, self.size.x, self.size.y
+            )
+        })
+    }
+}
+
+impl<T: fmt::Display + Copy> fmt::Display for Matrix2D<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut current = Point2D::default();
+        for i in 0..self.size.y {
+            current.y = i;
+            write!(f, "[").expect("didn't write!");
+            for j in 0..self.size.x {
+                current.x = j;
+                write!(f, "{} ", self.get(&current)).expect("didn't write!");
+            }
+            writeln!(f, "]").expect("didn't write!");
+        }
+        Ok(())
+    }
+}
+
+// The smallest box holding a set of points, with both corners inside it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Bounds<P> {
+    pub min: P,
+    pub max: P,
+}
+
+impl<P: ArrayLike + Copy> Bounds<P> {
+    pub fn new(point: P) -> Bounds<P> {
+        Bounds {
+            min: point,
+            max: point,
+        }
+    }
+
+    // Grows the box just enough to hold |point|.
+    pub fn include(&mut self, point: &P) {
+        for i in 0..P::size() {
+            self.min.set(i, self.min.get(i).min(point.get(i)));
+            self.max.set(i, self.max.get(i).max(point.get(i)));
+        }
+    }
+
+    pub fn contains(&self, point: &P) -> bool {
+        (0..P::size()).all(|i| (self.min.get(i)..=self.max.get(i)).contains(&point.get(i)))
+    }
+
+    // How many points fit along dimension |i|.
+    pub fn extent(&self, i: usize) -> i64 {
+        self.max.get(i) - self.min.get(i) + 1
+    }
+
+    pub fn volume(&self) -> usize {
+        (0..P::size()).map(|i| self.extent(i) as usize).product()
+    }
+
+    fn on_edge(&self, point: &P) -> bool {
+        (0..P::size()).any(|i| point.get(i) == self.min.get(i) || point.get(i) == self.max.get(i))
+    }
+
+    // Position of |point| in a row-major array covering the box, the first
+    // dimension changing fastest. None if it's outside.
+    fn index_of(&self, point: &P) -> Option<usize> {
+        let mut index = 0;
+        for i in (0..P::size()).rev() {
+            let (offset, extent) = (point.get(i) - self.min.get(i), self.extent(i));
+            if !(0..extent).contains(&offset) {
+                return None;
+            }
+            index = index * extent as usize + offset as usize;
+        }
+        Some(index)
+    }
+
+    // Moves |point| to the one after it in the same order as |index_of|,
+    // cheaper than |point_at| when walking every point.
+    fn next_point(&self, point: &mut P) {
+        for i in 0..P::size() {
+            if point.get(i) < self.max.get(i) {
+                point.set(i, point.get(i) + 1);
+                return;
+            }
+            point.set(i, self.min.get(i));
+        }
+    }
+
+    fn point_at(&self, mut index: usize) -> P {
+        let mut point = self.min;
+        for i in 0..P::size() {
+            let extent = self.extent(i) as usize;
+            point.set(i, self.min.get(i) + (index % extent) as i64);
+            index /= extent;
+        }
+        point
+    }
+}
+
+impl Bounds<Point2D> {
+    pub fn size(&self) -> Point2D {
+        self.max - self.min + Point2D { x: 1, y: 1 }
+    }
+}
+
+// Cells at arbitrary points, of any number of dimensions, that only take up
+// memory for the points that are set. Keeps track of the box around them as
+// they come and go.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseGrid<P: Eq + Hash, T> {
+    cells: HashMap<P, T>,
+    bounds: Option<Bounds<P>>,
+}
+
+impl<P: Eq + Hash, T> Default for SparseGrid<P, T> {
+    fn default() -> Self {
+        SparseGrid {
+            cells: HashMap::new(),
+            bounds: None,
+        }
+    }
+}
+
+impl<P: ArrayLike + Copy + Eq + Hash, T> SparseGrid<P, T> {
+    pub fn new() -> Self {
+        SparseGrid::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    // The box around every point that is set, None if there are none.
+    pub fn bounds(&self) -> Option<Bounds<P>> {
+        self.bounds
+    }
+
+    pub fn contains(&self, point: &P) -> bool {
+        self.cells.contains_key(point)
+    }
+
+    pub fn get(&self, point: &P) -> Option<&T> {
+        self.cells.get(point)
+    }
+
+    pub fn get_mut(&mut self, point: &P) -> Option<&mut T> {
+        self.cells.get_mut(point)
+    }
+
+    // Returns the value that was at |point| before, if any.
+    pub fn insert(&mut self, point: P, value: T) -> Option<T> {
+        match &mut self.bounds {
+            Some(bounds) => bounds.include(&point),
+            None => self.bounds = Some(Bounds::new(point)),
+        }
+        self.cells.insert(point, value)
+    }
+
+    pub fn remove(&mut self, point: &P) -> Option<T> {
+        let removed = self.cells.remove(point);
+        // Only points on the edge of the box can shrink it.
+        if removed.is_some() && self.bounds.is_some_and(|b| b.on_edge(point)) {
+            self.bounds = self.compute_bounds();
+        }
+        removed
+    }
+
+    fn compute_bounds(&self) -> Option<Bounds<P>> {
+        let mut points = self.cells.keys();
+        let mut bounds = Bounds::new(*points.next()?);
+        for point in points {
+            bounds.include(point);
+        }
+        Some(bounds)
+    }
+
+    // In no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = (&P, &T)> {
+        self.cells.iter()
+    }
+
+    pub fn points(&self) -> impl Iterator<Item = &P> {
+        self.cells.keys()
+    }
+}
+
+impl<P: ArrayLike + Copy + Eq + Hash, T> FromIterator<(P, T)> for SparseGrid<P, T> {
+    fn from_iter<I: IntoIterator<Item = (P, T)>>(iter: I) -> Self {
+        let mut grid = SparseGrid::new();
+        for (point, value) in iter {
+            grid.insert(point, value);
+        }
+        grid
+    }
+}
+
+impl<T: Clone> SparseGrid<Point2D, T> {
+    // The |size| rectangle with its top left corner at |origin|, with
+    // |empty| wherever nothing is set.
+    pub fn window(&self, origin: Point2D, size: Point2D, empty: T) -> Matrix2D<T> {
+        Matrix2D::from_fn(size, |p| {
+            self.get(&(origin + p))
+                .cloned()
+                .unwrap_or_else(|| empty.clone())
+        })
+    }
+
+    // Everything inside |bounds|, so the top left corner of the result is
+    // at |bounds().min|.
+    pub fn to_matrix(&self, empty: T) -> Matrix2D<T> {
+        match self.bounds {
+            Some(bounds) => self.window(bounds.min, bounds.size(), empty),
+            None => Matrix2D {
+                data: Vec::new(),
+                size: Point2D::default(),
+            },
+        }
+    }
+}
+
+// Draws everything inside |bounds| a row per line, with '.' wherever nothing
+// is set.
+impl<T: fmt::Display> fmt::Display for SparseGrid<Point2D, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bounds = match self.bounds {
+            Some(bounds) => bounds,
+            None => return Ok(()),
+        };
+        for y in bounds.min.y..=bounds.max.y {
+            for x in bounds.min.x..=bounds.max.x {
+                match self.get(&Point2D { x, y }) {
+                    Some(value) => write!(f, "{}", value)?,
+                    None => write!(f, ".")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+// Like |SparseGrid|, but stores every cell in the box around the points that
+// were set, so looking cells up is cheap while growing is not. Whenever a
+// point outside of the box is set, the box grows towards it by an extra
+// |padding| so that the next few don't need to reallocate too.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DenseGrid<P, T> {
+    data: Vec<T>,
+    region: Option<Bounds<P>>,
+    empty: T,
+    padding: i64,
+}
+
+impl<P: ArrayLike + Copy, T: Clone> DenseGrid<P, T> {
+    // Every cell starts out as |empty|.
+    pub fn new(empty: T, padding: i64) -> Self {
+        assert!(padding >= 0, "padding can't be negative");
+        DenseGrid {
+            data: Vec::new(),
+            region: None,
+            empty,
+            padding,
+        }
+    }
+
+    // The box that is currently allocated, None until something is set.
+    pub fn region(&self) -> Option<Bounds<P>> {
+        self.region
+    }
+
+    // |empty| outside of the region.
+    pub fn get(&self, point: &P) -> &T {
+        self.region
+            .and_then(|r| r.index_of(point))
+            .map_or(&self.empty, |i| &self.data[i])
+    }
+
+    pub fn set(&mut self, point: P, value: T) {
+        let index = match self.region.and_then(|r| r.index_of(&point)) {
+            Some(index) => index,
+            None => {
+                self.grow(&point);
+                self.region.unwrap().index_of(&point).unwrap()
+            }
+        };
+        self.data[index] = value;
+    }
+
+    fn grow(&mut self, point: &P) {
+        let mut region = match self.region {
+            Some(region) => region,
+            None => Bounds::new(*point),
+        };
+        // A new region pads every side, an existing one only the sides the
+        // point is past.
+        let fresh = self.region.is_none();
+        for i in 0..P::size() {
+            if fresh || point.get(i) < region.min.get(i) {
+                region.min.set(i, point.get(i) - self.padding);
+            }
+            if fresh || point.get(i) > region.max.get(i) {
+                region.max.set(i, point.get(i) + self.padding);
+            }
+        }
+
+        let mut data = vec![self.empty.clone(); region.volume()];
+        if let Some(old) = self.region {
+            for (i, value) in self.data.drain(..).enumerate() {
+                data[region.index_of(&old.point_at(i)).unwrap()] = value;
+            }
+        }
+        self.data = data;
+        self.region = Some(region);
+    }
+
+    // Every cell in the region, the first dimension changing fastest.
+    pub fn iter(&self) -> impl Iterator<Item = (P, &T)> {
+        let region = self.region;
+        let mut point = region.map(|r| r.min);
+        self.data.iter().map(move |value| {
+            let current = point.unwrap();
+            region.unwrap().next_point(point.as_mut().unwrap());
+            (current, value)
+        })
+    }
+
+    // Same order as |iter|, without the points.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.data.iter_mut()
+    }
+}
+
+impl<T: Clone> DenseGrid<Point2D, T> {
+    // Takes over every cell of |matrix|, with its top left corner at the
+    // origin, without growing one point at a time.
+    pub fn from_matrix(matrix: Matrix2D<T>, empty: T, padding: i64) -> Self {
+        let mut grid = DenseGrid::new(empty, padding);
+        if !matrix.data.is_empty() {
+            grid.region = Some(Bounds {
+                min: Point2D::default(),
+                max: matrix.size - Point2D { x: 1, y: 1 },
+            });
+            grid.data = matrix.data;
+        }
+        grid
+    }
+
+    // The whole region, so the top left corner of the result is at
+    // |region().min|.
+    pub fn to_matrix(&self) -> Matrix2D<T> {
+        match self.region {
+            Some(region) => Matrix2D {
+                data: self.data.clone(),
+                size: region.size(),
+            },
+            None => Matrix2D {
+                data: Vec::new(),
+                size: Point2D::default(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
     pub fn test_advance_in_place() {
         let mut point = Point2D { x: 0, y: 0 };
         point.advance(Direction::Left);
@@ -405,6 +1937,132 @@ mod tests {
         assert_eq!(Point2D { x: 0, y: -1 }, copy_up);
     }
 
+    #[test]
+    pub fn test_coordinate_systems() {
+        let screen = CoordinateSystem::Screen;
+        let compass = CoordinateSystem::Compass;
+        assert_eq!(Point2D { x: 0, y: -1 }, screen.offset(Direction::NORTH));
+        assert_eq!(Point2D { x: 0, y: 1 }, compass.offset(Direction::NORTH));
+        assert_eq!(Point2D { x: 1, y: 0 }, screen.offset(Direction::EAST));
+        assert_eq!(Point2D { x: 1, y: 0 }, compass.offset(Direction::EAST));
+        assert_eq!(
+            Point2D { x: -1, y: -1 },
+            compass.offset(Direction8::SouthWest)
+        );
+        assert_eq!(
+            Point2D { x: 3, y: -6 },
+            compass.advance(Point2D { x: 3, y: 0 }, Direction::SOUTH, 6)
+        );
+
+        // Turning left from east faces north in both systems, and a turned
+        // point stays the offset of the same turned direction.
+        for system in [screen, compass].iter() {
+            let mut point = system.offset(Direction::EAST);
+            system.rotate_left(&mut point, 90).unwrap();
+            assert_eq!(system.offset(Direction::NORTH), point);
+            system.rotate_right(&mut point, 180).unwrap();
+            assert_eq!(system.offset(Direction::SOUTH), point);
+
+            for &degrees in [90, 180, -90].iter() {
+                let mut direction = Direction::EAST;
+                let mut point = system.offset(direction);
+                direction.rotate_left(degrees).unwrap();
+                system.rotate_left(&mut point, degrees).unwrap();
+                assert_eq!(system.offset(direction), point);
+            }
+        }
+
+        assert_eq!(
+            vec!["north", "east", "south", "west"],
+            Direction::ALL
+                .iter()
+                .map(|d| d.compass_name())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    pub fn test_direction8() {
+        assert_eq!(8, Direction8::iter().count());
+        assert_eq!(Direction8::NorthWest, Direction8::North.to_left());
+        assert_eq!(Direction8::North, Direction8::NorthWest.to_right());
+        assert_eq!(Direction8::SouthWest, Direction8::NorthEast.inverse());
+        for direction in Direction8::iter() {
+            assert_eq!(direction, direction.to_left().to_right());
+            assert_eq!(-direction.offset(), direction.inverse().offset());
+            assert_eq!(
+                1,
+                direction.offset().chebyshev_distance(&Point2D::default())
+            );
+        }
+        assert_eq!(Direction8::East, Direction8::from(Direction::Right));
+        assert_eq!(
+            Direction8::from(Direction::Up).offset(),
+            Point2D::default().advance_copy(Direction::Up)
+        );
+
+        let mut point = Point2D::default();
+        point.advance(Direction8::NorthEast);
+        point.advance_mult(Direction8::South, 3);
+        assert_eq!(Point2D { x: 1, y: 2 }, point);
+    }
+
+    #[test]
+    pub fn test_hex_direction() {
+        assert_eq!(6, HexDirection::iter().count());
+        assert_eq!(HexDirection::NorthEast, HexDirection::East.to_left());
+        assert_eq!(HexDirection::SouthEast, HexDirection::East.to_right());
+        assert_eq!(HexDirection::NorthWest, HexDirection::SouthEast.inverse());
+        let mut point = Point3D::default();
+        for direction in HexDirection::iter() {
+            let offset = direction.offset();
+            assert_eq!(0, offset.x + offset.y + offset.z);
+            point.advance_mult(direction, 2);
+        }
+        // Going all the way around ends up where we started.
+        assert_eq!(Point3D::default(), point);
+        assert_eq!(
+            Point3D { x: 0, y: 2, z: -2 },
+            point.advance_copy_mult(HexDirection::NorthWest, 2)
+        );
+    }
+
+    #[test]
+    pub fn test_parse_directions() {
+        assert_eq!(
+            Ok(Direction8::NorthEast),
+            "ne".parse::<Direction8>().map_err(|e| e.to_string())
+        );
+        assert_eq!(
+            Ok(Direction8::SouthWest),
+            "sw".parse::<Direction8>().map_err(|e| e.to_string())
+        );
+        assert_eq!(
+            Err("unknown direction 'SW'".to_owned()),
+            "SW".parse::<Direction8>().map_err(|e| e.to_string())
+        );
+        assert_eq!(
+            Ok(Direction8::West),
+            "w".parse::<Direction8>().map_err(|e| e.to_string())
+        );
+        assert_eq!(
+            Err("unknown direction 'up'".to_owned()),
+            "up".parse::<Direction8>().map_err(|e| e.to_string())
+        );
+        assert_eq!(
+            Ok(HexDirection::SouthEast),
+            "se".parse::<HexDirection>().map_err(|e| e.to_string())
+        );
+        assert_eq!(
+            Err("unknown hex direction 'n'".to_owned()),
+            "n".parse::<HexDirection>().map_err(|e| e.to_string())
+        );
+        assert_eq!(
+            Err("unknown hex direction 'E'".to_owned()),
+            "E".parse::<HexDirection>().map_err(|e| e.to_string())
+        );
+    }
+
     #[test]
     pub fn test_rotation() {
         let mut point = Point2D { x: 10, y: 4 };
@@ -416,6 +2074,451 @@ mod tests {
         assert_eq!(Point2D { x: -10, y: -4 }, point);
     }
 
+    #[test]
+    pub fn test_rotation_by_degrees() {
+        let point = Point2D { x: 10, y: 4 };
+        let rotated = |degrees| {
+            let mut p = point;
+            p.rotate_left(degrees).map(|_| p)
+        };
+        assert_eq!(Ok(point), rotated(0));
+        assert_eq!(Ok(Point2D { x: -4, y: 10 }), rotated(90));
+        assert_eq!(Ok(Point2D { x: -10, y: -4 }), rotated(180));
+        assert_eq!(rotated(270), rotated(-90));
+        assert_eq!(rotated(90), rotated(450));
+        assert_eq!(rotated(-630), rotated(90));
+        assert_eq!(Err(InvalidAngle(45)), rotated(45));
+
+        let mut p = point;
+        p.rotate_right(90).unwrap();
+        assert_eq!(Point2D { x: 4, y: -10 }, p);
+        assert_eq!(
+            "-100 degrees is not a multiple of 90",
+            p.rotate_right(100).unwrap_err().to_string()
+        );
+        // Failed rotations leave the point alone.
+        assert_eq!(Point2D { x: 4, y: -10 }, p);
+
+        let mut direction = Direction::Up;
+        direction.rotate_left(270).unwrap();
+        assert_eq!(Direction::Right, direction);
+        direction.rotate_right(180).unwrap();
+        assert_eq!(Direction::Left, direction);
+    }
+
+    #[test]
+    pub fn test_rotation_about_pivot() {
+        let pivot = Point2D { x: 2, y: 3 };
+        let mut point = Point2D { x: 5, y: 3 };
+        point.rotate_left_about(pivot, 90).unwrap();
+        assert_eq!(Point2D { x: 2, y: 6 }, point);
+        point.rotate_right_about(pivot, 180).unwrap();
+        assert_eq!(Point2D { x: 2, y: 0 }, point);
+        point.rotate_left_about(point, 90).unwrap();
+        assert_eq!(Point2D { x: 2, y: 0 }, point);
+    }
+
+    #[test]
+    pub fn test_rotations_agree() {
+        // A matrix with one cell right of its centre turns like the direction
+        // pointing at that cell, and like the point on a screen.
+        let centre = Point2D { x: 1, y: 1 };
+        let marked = |matrix: &Matrix2D<char>| {
+            matrix
+                .enumerate()
+                .find(|(_, &cell)| cell == '#')
+                .map(|(point, _)| point - centre)
+                .unwrap()
+        };
+        for &degrees in [0, 90, 180, 270, -90, 450].iter() {
+            let mut point = Direction::Right.offset();
+            let mut direction = Direction::Right;
+            let mut matrix = letters(&["...", "..#", "..."]);
+            CoordinateSystem::Screen
+                .rotate_left(&mut point, degrees)
+                .unwrap();
+            direction.rotate_left(degrees).unwrap();
+            matrix.rotate_left(degrees).unwrap();
+            assert_eq!(point, direction.offset());
+            assert_eq!(point, marked(&matrix));
+        }
+        let mut point = Direction::Right.offset();
+        point.rotate_left(90).unwrap();
+        assert_eq!(Direction::Down.offset(), point);
+    }
+
+    #[test]
+    pub fn test_reflection() {
+        let point = Point2D { x: 3, y: -5 };
+        assert_eq!(Point2D { x: 3, y: 5 }, point.reflect_copy(Axis::X));
+        assert_eq!(Point2D { x: -3, y: -5 }, point.reflect_copy(Axis::Y));
+        assert_eq!(Point2D { x: -5, y: 3 }, point.reflect_copy(Axis::Diagonal));
+        assert_eq!(
+            Point2D { x: 5, y: -3 },
+            point.reflect_copy(Axis::AntiDiagonal)
+        );
+        let mut twice = point;
+        twice.reflect(Axis::Diagonal);
+        twice.reflect(Axis::Diagonal);
+        assert_eq!(point, twice);
+    }
+
+    fn letters(rows: &[&str]) -> Matrix2D<char> {
+        Matrix2D::create_with_data(
+            &Point2D {
+                x: rows[0].len() as i64,
+                y: rows.len() as i64,
+            },
+            rows.iter().flat_map(|r| r.chars()).collect(),
+        )
+    }
+
+    #[test]
+    pub fn test_matrix_transformations() {
+        let matrix = letters(&["abc", "def"]);
+        assert_eq!(letters(&["ad", "be", "cf"]), matrix.transpose_copy());
+        assert_eq!(letters(&["cba", "fed"]), matrix.flip_horizontal_copy());
+        assert_eq!(letters(&["def", "abc"]), matrix.flip_vertical_copy());
+
+        let mut rotated = matrix.clone();
+        rotated.rotate_90_deg_right();
+        assert_eq!(letters(&["da", "eb", "fc"]), rotated);
+        rotated.rotate_right(180).unwrap();
+        assert_eq!(letters(&["cf", "be", "ad"]), rotated);
+        rotated.rotate_left(-90).unwrap();
+        assert_eq!(matrix, rotated);
+        rotated.rotate_180_deg();
+        assert_eq!(letters(&["fed", "cba"]), rotated);
+
+        assert_eq!(
+            letters(&["fc", "eb", "da"]),
+            matrix.reflect_copy(Axis::AntiDiagonal)
+        );
+        assert_eq!(matrix.flip_vertical_copy(), matrix.reflect_copy(Axis::X));
+    }
+
+    #[test]
+    pub fn test_matrix_orientations() {
+        let matrix = letters(&["ab", "cd"]);
+        let orientations = matrix.orientations();
+        assert_eq!(8, orientations.len());
+        assert_eq!(matrix, orientations[0]);
+        assert_eq!(letters(&["ca", "db"]), orientations[1]);
+        assert_eq!(letters(&["ba", "dc"]), orientations[4]);
+        // A matrix without any symmetry has eight distinct orientations.
+        for (i, a) in orientations.iter().enumerate() {
+            assert!(orientations[i + 1..].iter().all(|b| a != b));
+        }
+        assert!(orientations.contains(&matrix.transpose_copy()));
+    }
+
+    #[test]
+    pub fn test_matrix_views() {
+        let matrix = letters(&["abc", "def", "ghi"]);
+        let collect = |view: MatrixView<char>| view.iter().collect::<String>();
+        assert_eq!("def", collect(matrix.row(1).unwrap()));
+        assert_eq!("cfi", collect(matrix.column(2).unwrap()));
+        assert_eq!("abc", collect(matrix.border(Direction::Up).unwrap()));
+        assert_eq!("ghi", collect(matrix.border(Direction::Down).unwrap()));
+        assert_eq!("adg", collect(matrix.border(Direction::Left).unwrap()));
+        assert_eq!("cfi", collect(matrix.border(Direction::Right).unwrap()));
+        assert!(matrix.row(3).is_none());
+        assert!(matrix.column(-1).is_none());
+
+        let window = matrix
+            .window(Point2D { x: 1, y: 1 }, Point2D { x: 2, y: 2 })
+            .unwrap();
+        assert_eq!("efhi", collect(window));
+        assert_eq!('h', window[Point2D { x: 0, y: 1 }]);
+        assert_eq!(None, window.try_get(&Point2D { x: 2, y: 0 }));
+        assert_eq!(letters(&["ef", "hi"]), window.to_matrix());
+        assert!(matrix
+            .window(Point2D { x: 2, y: 2 }, Point2D { x: 2, y: 1 })
+            .is_none());
+
+        assert!(Matrix2D::<char>::default().border(Direction::Up).is_none());
+    }
+
+    #[test]
+    pub fn test_matrix_stitching() {
+        let stitched = Matrix2D::stitch(&[
+            vec![letters(&["ab", "cd"]), letters(&["e", "f"])],
+            vec![letters(&["ghi"])],
+        ]);
+        assert_eq!(letters(&["abe", "cdf", "ghi"]), stitched);
+        assert_eq!(Point2D::default(), Matrix2D::<char>::stitch(&[]).size);
+    }
+
+    #[test]
+    #[should_panic(expected = "rows need the same width")]
+    pub fn test_matrix_stitching_mismatched_rows() {
+        Matrix2D::stitch(&[vec![letters(&["ab"])], vec![letters(&["c"])]]);
+    }
+
+    #[test]
+    pub fn test_matrix_from_fn() {
+        let matrix = Matrix2D::from_fn(Point2D { x: 3, y: 2 }, |p| p.x * 10 + p.y);
+        assert_eq!(vec![0, 10, 20, 1, 11, 21], matrix.data);
+    }
+
+    #[test]
+    pub fn test_matrix_iterators() {
+        let matrix = letters(&["abc", "def"]);
+        assert_eq!(
+            vec![(0, 0), (1, 0), (2, 0), (0, 1), (1, 1), (2, 1)],
+            matrix.iter_points().map(|p| (p.x, p.y)).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            Some((Point2D { x: 1, y: 1 }, &'e')),
+            matrix.enumerate().nth(4)
+        );
+        let text = |views: Vec<MatrixView<char>>| {
+            views
+                .iter()
+                .map(|v| v.iter().collect::<String>())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(vec!["abc", "def"], text(matrix.rows().collect()));
+        assert_eq!(vec!["ad", "be", "cf"], text(matrix.columns().collect()));
+        assert_eq!(0, Matrix2D::<char>::default().rows().count());
+    }
+
+    #[test]
+    pub fn test_matrix_neighbors() {
+        let matrix = letters(&["abc", "def", "ghi"]);
+        let values = |neighbors: Vec<(Point2D, &char)>| {
+            neighbors.iter().map(|(_, c)| **c).collect::<String>()
+        };
+        let center = Point2D { x: 1, y: 1 };
+        assert_eq!("bfhd", values(matrix.neighbors4(&center).collect()));
+        assert_eq!("bcfihgda", values(matrix.neighbors8(&center).collect()));
+        let corner = Point2D { x: 0, y: 0 };
+        assert_eq!("bd", values(matrix.neighbors4(&corner).collect()));
+        assert_eq!("bed", values(matrix.neighbors8(&corner).collect()));
+        assert_eq!(
+            Some((Point2D { x: 1, y: 0 }, &'b')),
+            matrix.neighbors4(&corner).next()
+        );
+        // Locations outside of the matrix can still have neighbors inside.
+        assert_eq!(
+            "a",
+            values(matrix.neighbors4(&Point2D { x: -1, y: 0 }).collect())
+        );
+    }
+
+    #[test]
+    pub fn test_matrix_rays() {
+        let matrix = letters(&["a.b.", "....", "c..d"]);
+        let values = |ray: Vec<(Point2D, &char)>| ray.iter().map(|(_, c)| **c).collect::<String>();
+        let origin = Point2D::default();
+        assert_eq!(
+            ".b.",
+            values(matrix.ray(&origin, Direction::Right).collect())
+        );
+        assert_eq!(
+            "..",
+            values(matrix.ray(&origin, Direction8::SouthEast).collect())
+        );
+        assert_eq!("", values(matrix.ray(&origin, Direction::Up).collect()));
+        assert_eq!(
+            ".b",
+            values(
+                matrix
+                    .ray_until(&origin, Direction::Right, |c| *c != '.')
+                    .collect()
+            )
+        );
+        assert_eq!(
+            Some((Point2D { x: 0, y: 2 }, &'c')),
+            matrix
+                .ray_until(&origin, Direction::Down, |c| *c != '.')
+                .last()
+        );
+        // Never stopping is the same as |ray|.
+        assert_eq!(
+            "..",
+            values(
+                matrix
+                    .ray_until(&origin, Direction8::SouthEast, |_| false)
+                    .collect()
+            )
+        );
+    }
+
+    #[test]
+    pub fn test_bounds() {
+        let mut bounds = Bounds::new(Point3D { x: 1, y: 2, z: 3 });
+        bounds.include(&Point3D { x: -1, y: 4, z: 3 });
+        assert_eq!(Point3D { x: -1, y: 2, z: 3 }, bounds.min);
+        assert_eq!(Point3D { x: 1, y: 4, z: 3 }, bounds.max);
+        assert_eq!(
+            (3, 3, 1),
+            (bounds.extent(0), bounds.extent(1), bounds.extent(2))
+        );
+        assert_eq!(9, bounds.volume());
+        assert!(bounds.contains(&Point3D { x: 0, y: 3, z: 3 }));
+        assert!(!bounds.contains(&Point3D { x: 0, y: 3, z: 4 }));
+        for i in 0..bounds.volume() {
+            assert_eq!(Some(i), bounds.index_of(&bounds.point_at(i)));
+        }
+    }
+
+    #[test]
+    pub fn test_sparse_grid() {
+        let mut grid = SparseGrid::new();
+        assert_eq!(None, grid.bounds());
+        grid.insert(Point::new([0, 0, 0, 0]), 'a');
+        grid.insert(Point::new([2, -1, 0, 5]), 'b');
+        assert_eq!(Some('a'), grid.insert(Point::new([0, 0, 0, 0]), 'c'));
+        assert_eq!(2, grid.len());
+        assert_eq!(Some(&'c'), grid.get(&Point::new([0, 0, 0, 0])));
+        assert_eq!(
+            Some(Bounds {
+                min: Point::new([0, -1, 0, 0]),
+                max: Point::new([2, 0, 0, 5])
+            }),
+            grid.bounds()
+        );
+
+        // Removing a point on the edge shrinks the box.
+        assert_eq!(Some('b'), grid.remove(&Point::new([2, -1, 0, 5])));
+        assert_eq!(Some(Bounds::new(Point::new([0, 0, 0, 0]))), grid.bounds());
+        grid.remove(&Point::new([0, 0, 0, 0]));
+        assert!(grid.is_empty());
+        assert_eq!(None, grid.bounds());
+    }
+
+    #[test]
+    pub fn test_sparse_grid_rendering() {
+        let grid: SparseGrid<Point2D, char> = vec![
+            (Point2D { x: -1, y: 5 }, '#'),
+            (Point2D { x: 1, y: 6 }, '#'),
+            (Point2D { x: 0, y: 7 }, '@'),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!("#..\n..#\n.@.\n", grid.to_string());
+        assert_eq!(letters(&["#  ", "  #", " @ "]), grid.to_matrix(' '));
+        assert_eq!(
+            letters(&["  ", " #", "  "]),
+            grid.window(Point2D { x: -2, y: 4 }, Point2D { x: 2, y: 3 }, ' ')
+        );
+        assert_eq!("", SparseGrid::<Point2D, char>::new().to_string());
+    }
+
+    #[test]
+    pub fn test_dense_grid() {
+        let mut grid = DenseGrid::new('.', 1);
+        assert_eq!(&'.', grid.get(&Point2D { x: 5, y: 5 }));
+        grid.set(Point2D { x: 0, y: 0 }, 'a');
+        assert_eq!(
+            Some(Bounds {
+                min: Point2D { x: -1, y: -1 },
+                max: Point2D { x: 1, y: 1 }
+            }),
+            grid.region()
+        );
+        // Close enough to fit in the padding, so nothing moves.
+        grid.set(Point2D { x: 1, y: -1 }, 'b');
+        assert_eq!(Point2D { x: 3, y: 3 }, grid.region().unwrap().size());
+
+        // Only grows towards the new point.
+        grid.set(Point2D { x: 3, y: 0 }, 'c');
+        assert_eq!(
+            Some(Bounds {
+                min: Point2D { x: -1, y: -1 },
+                max: Point2D { x: 4, y: 1 }
+            }),
+            grid.region()
+        );
+        assert_eq!(&'a', grid.get(&Point2D { x: 0, y: 0 }));
+        assert_eq!(&'b', grid.get(&Point2D { x: 1, y: -1 }));
+        assert_eq!(letters(&["..b...", ".a..c.", "......"]), grid.to_matrix());
+        assert_eq!(
+            Some((Point2D { x: 3, y: 0 }, &'c')),
+            grid.iter().find(|(_, c)| **c == 'c')
+        );
+        // Right on the bottom edge is already inside, so only x grows.
+        grid.set(Point2D { x: 6, y: 1 }, 'd');
+        assert_eq!(
+            Some(Bounds {
+                min: Point2D { x: -1, y: -1 },
+                max: Point2D { x: 7, y: 1 }
+            }),
+            grid.region()
+        );
+
+        let mut grid = DenseGrid::new(0, 0);
+        grid.set(Point::new([1, 1, 1]), 1);
+        grid.set(Point::new([-1, 0, 2]), 2);
+        assert_eq!(12, grid.region().unwrap().volume());
+        assert_eq!(&1, grid.get(&Point::new([1, 1, 1])));
+        assert_eq!(3, grid.iter().map(|(_, v)| v).sum::<i32>());
+
+        let matrix = letters(&["ab", "cd", "ef"]);
+        let grid = DenseGrid::from_matrix(matrix.clone(), '.', 0);
+        assert_eq!(Point2D { x: 2, y: 3 }, grid.region().unwrap().size());
+        assert_eq!(&'f', grid.get(&Point2D { x: 1, y: 2 }));
+        assert_eq!(&'.', grid.get(&Point2D { x: 2, y: 0 }));
+        assert_eq!(matrix, grid.to_matrix());
+        let empty = Matrix2D {
+            data: Vec::new(),
+            size: Point2D::default(),
+        };
+        assert_eq!(None, DenseGrid::from_matrix(empty, '.', 0).region());
+    }
+
+    fn small_matrix() -> Matrix2D<i64> {
+        // 0 1 2
+        // 3 4 5
+        Matrix2D::create_with_data(&Point2D { x: 3, y: 2 }, (0..6).collect())
+    }
+
+    #[test]
+    pub fn test_matrix_bounds() {
+        let mut matrix = small_matrix();
+        assert!(matrix.contains(&Point2D { x: 2, y: 1 }));
+        assert!(!matrix.contains(&Point2D { x: 3, y: 1 }));
+        assert!(!matrix.contains(&Point2D { x: -1, y: 0 }));
+        assert_eq!(Some(&5), matrix.try_get(&Point2D { x: 2, y: 1 }));
+        assert_eq!(None, matrix.try_get(&Point2D { x: 0, y: -1 }));
+        assert_eq!(None, matrix.try_get(&Point2D { x: 5, y: 0 }));
+
+        *matrix.get_mut(&Point2D { x: 1, y: 0 }).unwrap() = 10;
+        assert_eq!(None, matrix.get_mut(&Point2D { x: 1, y: 2 }));
+        matrix[Point2D { x: 0, y: 1 }] += 20;
+        assert_eq!(10, matrix[Point2D { x: 1, y: 0 }]);
+        assert_eq!(23, matrix.get(&Point2D { x: 0, y: 1 }));
+    }
+
+    #[test]
+    #[should_panic(expected = "(-1, 0) is outside of the 3x2 matrix")]
+    pub fn test_matrix_index_out_of_bounds() {
+        let _ = small_matrix()[Point2D { x: -1, y: 0 }];
+    }
+
+    #[test]
+    pub fn test_matrix_wrapping() {
+        let mut matrix = small_matrix();
+        let point = Point2D { x: -1, y: 3 };
+        assert_eq!(None, matrix.get_wrapping(&point, Wrap::NONE));
+        assert_eq!(None, matrix.get_wrapping(&point, Wrap::HORIZONTAL));
+        assert_eq!(None, matrix.get_wrapping(&point, Wrap::VERTICAL));
+        assert_eq!(Some(&5), matrix.get_wrapping(&point, Wrap::BOTH));
+        assert_eq!(
+            Some(&4),
+            matrix.get_wrapping(&Point2D { x: 7, y: 1 }, Wrap::HORIZONTAL)
+        );
+        assert_eq!(
+            Some(Point2D { x: 0, y: 0 }),
+            matrix.wrap(&Point2D { x: 0, y: -4 }, Wrap::VERTICAL)
+        );
+        *matrix
+            .get_wrapping_mut(&Point2D { x: 3, y: 0 }, Wrap::BOTH)
+            .unwrap() = 7;
+        assert_eq!(7, matrix[Point2D::default()]);
+    }
+
     #[test]
     pub fn test_scaling() {
         let mut point = Point2D { x: 5, y: 15 };
@@ -427,4 +2530,129 @@ mod tests {
         point.scale(0);
         assert_eq!(Point2D { x: 0, y: 0 }, point);
     }
+
+    #[test]
+    pub fn test_arithmetic() {
+        let a = Point2D { x: 7, y: -3 };
+        let b = Point2D { x: 2, y: 5 };
+        assert_eq!(Point2D { x: 5, y: -8 }, a - b);
+        assert_eq!(Point2D { x: -7, y: 3 }, -a);
+        assert_eq!(Point2D { x: 14, y: -6 }, a * 2);
+        assert_eq!(Point2D { x: 3, y: -1 }, a / 2);
+        let mut c = a;
+        c -= b;
+        assert_eq!(a - b, c);
+        c += b;
+        assert_eq!(a, c);
+    }
+
+    #[test]
+    pub fn test_distances() {
+        let a = Point2D { x: 7, y: -3 };
+        let b = Point2D { x: 2, y: 5 };
+        assert_eq!(13, a.manhattan_distance(&b));
+        assert_eq!(8, a.chebyshev_distance(&b));
+        assert_eq!(89, a.squared_euclidean_distance(&b));
+        assert_eq!(0, a.manhattan_distance(&a));
+        assert_eq!(a.manhattan_distance(&b), b.manhattan_distance(&a));
+    }
+
+    #[test]
+    pub fn test_products() {
+        let a = Point2D { x: 7, y: -3 };
+        let b = Point2D { x: 2, y: 5 };
+        assert_eq!(-1, a.dot(&b));
+        assert_eq!(41, a.cross(&b));
+        assert_eq!(-41, b.cross(&a));
+        assert_eq!(0, a.cross(&(a * 3)));
+    }
+
+    #[test]
+    pub fn test_components() {
+        let a = Point2D { x: 7, y: -3 };
+        let b = Point2D { x: 2, y: 5 };
+        assert_eq!(Point2D { x: 1, y: -1 }, a.signum());
+        assert_eq!(Point2D { x: 0, y: 1 }, Point2D { x: 0, y: 9 }.signum());
+        assert_eq!(Point2D { x: 2, y: -3 }, a.component_min(&b));
+        assert_eq!(Point2D { x: 7, y: 5 }, a.component_max(&b));
+    }
+
+    #[test]
+    pub fn test_ordering() {
+        let mut points = vec![
+            Point2D { x: 1, y: 2 },
+            Point2D { x: 0, y: 5 },
+            Point2D { x: 1, y: -1 },
+        ];
+        points.sort();
+        assert_eq!(
+            vec![
+                Point2D { x: 0, y: 5 },
+                Point2D { x: 1, y: -1 },
+                Point2D { x: 1, y: 2 },
+            ],
+            points
+        );
+    }
+
+    #[test]
+    pub fn test_point_arithmetic() {
+        let a = Point::new([1, -2, 3]);
+        let b = Point::new([4, 5, -6]);
+        assert_eq!(Point::new([5, 3, -3]), a + b);
+        assert_eq!(Point::new([-3, -7, 9]), a - b);
+        assert_eq!(Point::new([-1, 2, -3]), -a);
+        assert_eq!(Point::new([3, -6, 9]), a * 3);
+        let mut c = a;
+        c -= b;
+        c += b;
+        assert_eq!(a, c);
+        assert_eq!(-2, a[1]);
+        assert_eq!("(1, -2, 3)", a.to_string());
+    }
+
+    #[test]
+    pub fn test_point_distances() {
+        let a = Point::new([1, -2, 3, 0]);
+        let b = Point::new([4, 5, -6, 0]);
+        assert_eq!(19, a.manhattan_distance(&b));
+        assert_eq!(9, a.chebyshev_distance(&b));
+        assert_eq!(0, a.chebyshev_distance(&a));
+    }
+
+    #[test]
+    pub fn test_neighbor_offsets() {
+        assert_eq!(8, Point::<2>::neighbor_offsets().len());
+        assert_eq!(26, Point::<3>::neighbor_offsets().len());
+        assert_eq!(80, Point::<4>::neighbor_offsets().len());
+        assert!(Point::<4>::neighbor_offsets()
+            .iter()
+            .all(|o| o.chebyshev_distance(&Point::default()) == 1));
+        assert_eq!(
+            vec![
+                Point::new([-1, 0]),
+                Point::new([1, 0]),
+                Point::new([0, -1]),
+                Point::new([0, 1])
+            ],
+            Point::<2>::orthogonal_offsets()
+        );
+    }
+
+    #[test]
+    pub fn test_point_conversions() {
+        let point = Point2D { x: 3, y: -4 };
+        assert_eq!(Point::new([3, -4]), Point::from(point));
+        assert_eq!(point, Point2D::from(Point::from(point)));
+        let point = Point3D { x: 1, y: 2, z: 3 };
+        assert_eq!(point, Point::from(point).into());
+        let point = Point4D {
+            x: 1,
+            y: 2,
+            z: 3,
+            w: 4,
+        };
+        assert_eq!(point, Point::from(point).into());
+        assert_eq!("(1, 2, 3, 4)", point.to_string());
+    }
 }